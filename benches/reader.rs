@@ -0,0 +1,157 @@
+//! Compares the reads of the original reader, which went through one bounds
+//! checked byte at a time and `concat_number`, with the current bulk paths on
+//! synthetic inputs.
+//!
+//! Run with `cargo bench --bench reader`.
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use wiiu::binary_reader::{BinaryRead, BinaryReader, Endian, Parsable};
+use wiiu::binary_writer::{BinaryWriter, Writable};
+use wiiu::formats::nbt::{Tag, TagPayload};
+
+const SIZE: usize = 16 << 20;
+const ROUNDS: u32 = 5;
+
+/// The reader as it was before bulk reads, kept as the baseline.
+mod legacy {
+    use wiiu::binary_reader::Endian;
+
+    fn concat_number(high: u128, low: u128, shift: u8) -> u128 {
+        (high << shift) | low
+    }
+
+    pub struct BinaryReader<'a> {
+        pub data: &'a [u8],
+        pub offset: usize,
+        pub endian: Endian,
+    }
+
+    impl BinaryReader<'_> {
+        fn concat(&self, high: u128, low: u128, shift: u8) -> u128 {
+            if self.endian == Endian::Big {
+                concat_number(high, low, shift)
+            } else {
+                concat_number(low, high, shift)
+            }
+        }
+
+        pub fn read_u8(&mut self) -> u8 {
+            let result = self.data[self.offset];
+            self.offset += 1;
+            result
+        }
+
+        pub fn read_u16(&mut self) -> u16 {
+            let high = self.read_u8() as u128;
+            let low = self.read_u8() as u128;
+            self.concat(high, low, 8) as u16
+        }
+
+        pub fn read_u32(&mut self) -> u32 {
+            let high = self.read_u16() as u128;
+            let low = self.read_u16() as u128;
+            self.concat(high, low, 16) as u32
+        }
+
+        pub fn read_n_bytes(&mut self, size: usize) -> Vec<u8> {
+            let mut result = Vec::new();
+            for _ in 0..size {
+                result.push(self.read_u8());
+            }
+            result
+        }
+    }
+}
+
+fn legacy_reader(data: &[u8]) -> legacy::BinaryReader<'_> {
+    legacy::BinaryReader {
+        data,
+        offset: 0,
+        endian: Endian::Big,
+    }
+}
+
+fn measure<F: FnMut()>(mut f: F) -> Duration {
+    f();
+    let start = Instant::now();
+    for _ in 0..ROUNDS {
+        f();
+    }
+    start.elapsed() / ROUNDS
+}
+
+fn report(name: &str, bytes: usize, baseline: Duration, bulk: Duration) {
+    let throughput = |time: Duration| bytes as f64 / time.as_secs_f64() / (1 << 20) as f64;
+    println!(
+        "{:<12} legacy {:>9.1} MiB/s  bulk {:>9.1} MiB/s  speedup {:>6.1}x",
+        name,
+        throughput(baseline),
+        throughput(bulk),
+        baseline.as_secs_f64() / bulk.as_secs_f64()
+    );
+}
+
+fn bench_bytes(data: &[u8]) {
+    let baseline = measure(|| {
+        let mut reader = legacy_reader(data);
+        black_box(reader.read_n_bytes(data.len()));
+    });
+    let bulk = measure(|| {
+        let mut reader = BinaryReader::new(data);
+        black_box(reader.read_n_bytes(data.len()).unwrap().to_vec());
+    });
+    report("bytes", data.len(), baseline, bulk);
+}
+
+fn bench_u32(data: &[u8]) {
+    let count = data.len() / 4;
+    let baseline = measure(|| {
+        let mut reader = legacy_reader(data);
+        let mut values = Vec::new();
+        for _ in 0..count {
+            values.push(reader.read_u32());
+        }
+        black_box(values);
+    });
+    let bulk = measure(|| {
+        let mut reader = BinaryReader::new(data);
+        black_box(reader.read_u32_array(count).unwrap());
+    });
+    report("u32", data.len(), baseline, bulk);
+}
+
+fn bench_int_array(count: usize) {
+    let tag = Tag {
+        name: "ints".to_string(),
+        value: TagPayload::IntArray((0..count as i32).collect()),
+    };
+    let mut writer = BinaryWriter::new();
+    tag.write(&mut writer).unwrap();
+    let data = writer.into_inner();
+
+    // the original TAG_Int_Array payload loop
+    let baseline = measure(|| {
+        let mut reader = legacy_reader(&data);
+        reader.offset = 1 + 2 + 4;
+        let size = reader.read_u32() as usize;
+        let mut array = Vec::new();
+        for _ in 0..size {
+            array.push(reader.read_u32() as i32);
+        }
+        black_box(array);
+    });
+    let bulk = measure(|| {
+        let mut reader = BinaryReader::new(&data);
+        black_box(Tag::parse(&mut reader));
+    });
+    report("IntArray", data.len(), baseline, bulk);
+}
+
+fn main() {
+    let data = (0..SIZE).map(|i| i as u8).collect::<Vec<_>>();
+    bench_bytes(&data);
+    bench_u32(&data);
+    bench_int_array(SIZE / 4);
+}
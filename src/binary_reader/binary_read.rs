@@ -0,0 +1,394 @@
+use super::{
+    CoverageReport, Diagnostic, DiagnosticCode, DiagnosticMode, Diagnostics, Endian, Limit,
+    ParseError, ParseErrorKind, ParseLimits, ParseResult, Severity, Span,
+};
+use std::borrow::Cow;
+
+/// Common interface of the readers, so that [`Parsable`](super::Parsable)
+/// implementations work over both in-memory buffers and streams.
+///
+/// Implementors only provide positioning, mode accessors and a raw
+/// [`BinaryRead::read_at`]; typed reads are built on top of those and may be
+/// overridden where a faster path exists.
+pub trait BinaryRead<'a> {
+    fn offset(&self) -> usize;
+    fn seek(&mut self, offset: usize);
+
+    fn endian(&self) -> Endian;
+    fn set_endian(&mut self, endian: Endian);
+    fn is_64bit(&self) -> bool;
+    fn set_64bit(&mut self, is_64bit: bool);
+
+    fn path(&self) -> &[String];
+    fn path_mut(&mut self) -> &mut Vec<String>;
+
+    fn diagnostics(&self) -> &Diagnostics;
+    fn diagnostics_mut(&mut self) -> &mut Diagnostics;
+
+    fn limits(&self) -> &ParseLimits;
+
+    fn spans(&self) -> Option<&[Span]>;
+    fn spans_mut(&mut self) -> &mut Option<Vec<Span>>;
+
+    /// Total length of the input in bytes.
+    fn len(&self) -> usize;
+
+    /// Reads `size` bytes at `pos` without moving the read position.
+    fn read_at(&mut self, pos: usize, size: usize) -> ParseResult<Cow<'a, [u8]>>;
+
+    /// Absolute position of local offset 0, non-zero for bounded windows.
+    fn base(&self) -> usize {
+        0
+    }
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn remaining(&self) -> usize {
+        self.len().saturating_sub(self.offset())
+    }
+
+    /// Builds an error at local `offset` that records the absolute position
+    /// and the current parser path.
+    fn error_at(&self, kind: ParseErrorKind, offset: usize, length: usize) -> ParseError {
+        ParseError {
+            kind,
+            offset: self.base() + offset,
+            length,
+            path: self.path().to_vec(),
+        }
+    }
+
+    fn error(&self, kind: ParseErrorKind, length: usize) -> ParseError {
+        self.error_at(kind, self.offset(), length)
+    }
+
+    fn invalid_data<T: ToString>(&self, message: T) -> ParseError {
+        self.error(ParseErrorKind::InvalidData(message.to_string()), 0)
+    }
+
+    fn eof_error(&self, pos: usize, size: usize) -> ParseError {
+        self.error_at(
+            ParseErrorKind::UnexpectedEof {
+                available: self.len().saturating_sub(pos),
+            },
+            pos,
+            size,
+        )
+    }
+
+    /// Fails with [`ParseErrorKind::UnexpectedEof`] unless `size` bytes are
+    /// available at `pos`.
+    fn check(&self, pos: usize, size: usize) -> ParseResult<()> {
+        match pos.checked_add(size) {
+            Some(end) if end <= self.len() => Ok(()),
+            _ => Err(self.eof_error(pos, size)),
+        }
+    }
+
+    fn limit_error(&self, limit: Limit, requested: usize, max: usize) -> ParseError {
+        self.error(
+            ParseErrorKind::LimitExceeded {
+                limit,
+                requested,
+                max,
+            },
+            0,
+        )
+    }
+
+    /// Checks a count read from the input before allocating `count` values
+    /// of `T` for it.
+    fn check_count<T>(&self, count: usize) -> ParseResult<usize> {
+        let limits = self.limits();
+        if count > limits.max_elements {
+            return Err(self.limit_error(Limit::Elements, count, limits.max_elements));
+        }
+        let size = count.saturating_mul(std::mem::size_of::<T>());
+        if size > limits.max_allocation {
+            return Err(self.limit_error(Limit::Allocation, size, limits.max_allocation));
+        }
+        Ok(count)
+    }
+
+    fn check_decompressed_size(&self, size: usize) -> ParseResult<usize> {
+        let max = self.limits().max_decompressed_size;
+        if size > max {
+            return Err(self.limit_error(Limit::DecompressedSize, size, max));
+        }
+        Ok(size)
+    }
+
+    /// Records a diagnostic about `length` bytes at local `offset`, or fails
+    /// with [`ParseErrorKind::Diagnostic`] if it is a warning and the reader
+    /// is in [`DiagnosticMode::Strict`].
+    fn report_at<T: ToString>(
+        &mut self,
+        severity: Severity,
+        code: DiagnosticCode,
+        offset: usize,
+        length: usize,
+        message: T,
+    ) -> ParseResult<()> {
+        match self.diagnostics().mode {
+            DiagnosticMode::Ignore => Ok(()),
+            DiagnosticMode::Strict if severity >= Severity::Warning => Err(self.error_at(
+                ParseErrorKind::Diagnostic(code, message.to_string()),
+                offset,
+                length,
+            )),
+            _ => {
+                let diagnostic = Diagnostic {
+                    severity,
+                    code,
+                    offset: self.base() + offset,
+                    length,
+                    message: message.to_string(),
+                    path: self.path().to_vec(),
+                };
+                self.diagnostics_mut().entries.push(diagnostic);
+                Ok(())
+            }
+        }
+    }
+
+    fn warn_at<T: ToString>(
+        &mut self,
+        code: DiagnosticCode,
+        offset: usize,
+        length: usize,
+        message: T,
+    ) -> ParseResult<()> {
+        self.report_at(Severity::Warning, code, offset, length, message)
+    }
+
+    /// Starts recording the span and parser path of every read, for
+    /// [`BinaryRead::coverage`]. Peeks and [`BinaryRead::read_at`] are not
+    /// recorded.
+    fn record_spans(&mut self) {
+        self.spans_mut().get_or_insert_with(Vec::new);
+    }
+
+    fn record_span(&mut self, pos: usize, size: usize) {
+        if self.spans_mut().is_none() {
+            return;
+        }
+        let span = Span {
+            offset: self.base() + pos,
+            length: size,
+            path: self.path().to_vec(),
+        };
+        if let Some(spans) = self.spans_mut() {
+            spans.push(span);
+        }
+    }
+
+    /// Reports which bytes of the input the recorded reads explained, or
+    /// `None` if recording is off.
+    fn coverage(&self) -> Option<CoverageReport> {
+        let start = self.base();
+        Some(CoverageReport::new(
+            start..start + self.len(),
+            self.spans()?,
+        ))
+    }
+
+    /// Runs `f` with `label` appended to the parser path, so that any error
+    /// raised inside carries it as a breadcrumb. Fails once the path is
+    /// deeper than [`ParseLimits::max_depth`].
+    fn scoped<T, S, F>(&mut self, label: S, f: F) -> ParseResult<T>
+    where
+        S: Into<String>,
+        F: FnOnce(&mut Self) -> ParseResult<T>,
+    {
+        let depth = self.path().len() + 1;
+        let max_depth = self.limits().max_depth;
+        if depth > max_depth {
+            return Err(self.limit_error(Limit::Depth, depth, max_depth));
+        }
+        self.path_mut().push(label.into());
+        let ret = f(self);
+        self.path_mut().pop();
+        ret
+    }
+
+    fn read(&mut self, pos: usize, size: usize) -> ParseResult<Cow<'a, [u8]>> {
+        let result = self.read_at(pos, size)?;
+        self.record_span(pos, size);
+        Ok(result)
+    }
+
+    fn read_n_bytes(&mut self, size: usize) -> ParseResult<Cow<'a, [u8]>> {
+        let offset = self.offset();
+        let result = self.read(offset, size)?;
+        self.seek(offset + size);
+        Ok(result)
+    }
+
+    fn read_array<const N: usize>(&mut self) -> ParseResult<[u8; N]> {
+        let bytes = self.read_n_bytes(N)?;
+        let mut ret = [0; N];
+        ret.copy_from_slice(&bytes);
+        Ok(ret)
+    }
+
+    fn peek_u8(&mut self) -> ParseResult<u8> {
+        let offset = self.offset();
+        Ok(self.read_at(offset, 1)?[0])
+    }
+
+    fn read_u8(&mut self) -> ParseResult<u8> {
+        Ok(self.read_array::<1>()?[0])
+    }
+
+    fn read_u16(&mut self) -> ParseResult<u16> {
+        let bytes = self.read_array()?;
+        Ok(match self.endian() {
+            Endian::Big => u16::from_be_bytes(bytes),
+            Endian::Little => u16::from_le_bytes(bytes),
+        })
+    }
+
+    fn read_u32(&mut self) -> ParseResult<u32> {
+        let bytes = self.read_array()?;
+        Ok(match self.endian() {
+            Endian::Big => u32::from_be_bytes(bytes),
+            Endian::Little => u32::from_le_bytes(bytes),
+        })
+    }
+
+    fn read_u64(&mut self) -> ParseResult<u64> {
+        let bytes = self.read_array()?;
+        Ok(match self.endian() {
+            Endian::Big => u64::from_be_bytes(bytes),
+            Endian::Little => u64::from_le_bytes(bytes),
+        })
+    }
+
+    fn read_u128(&mut self) -> ParseResult<u128> {
+        let bytes = self.read_array()?;
+        Ok(match self.endian() {
+            Endian::Big => u128::from_be_bytes(bytes),
+            Endian::Little => u128::from_le_bytes(bytes),
+        })
+    }
+
+    fn read_i8(&mut self) -> ParseResult<i8> {
+        Ok(self.read_u8()? as i8)
+    }
+
+    fn read_i16(&mut self) -> ParseResult<i16> {
+        Ok(self.read_u16()? as i16)
+    }
+
+    fn read_i32(&mut self) -> ParseResult<i32> {
+        Ok(self.read_u32()? as i32)
+    }
+
+    fn read_i64(&mut self) -> ParseResult<i64> {
+        Ok(self.read_u64()? as i64)
+    }
+
+    fn read_f32(&mut self) -> ParseResult<f32> {
+        Ok(f32::from_bits(self.read_u32()?))
+    }
+
+    fn read_f64(&mut self) -> ParseResult<f64> {
+        Ok(f64::from_bits(self.read_u64()?))
+    }
+
+    fn read_half(&mut self) -> ParseResult<u32> {
+        if self.is_64bit() {
+            self.read_u32()
+        } else {
+            Ok(self.read_u16()? as u32)
+        }
+    }
+
+    fn read_word(&mut self) -> ParseResult<u64> {
+        if self.is_64bit() {
+            self.read_u64()
+        } else {
+            Ok(self.read_u32()? as u64)
+        }
+    }
+
+    fn read_dword(&mut self) -> ParseResult<u128> {
+        if self.is_64bit() {
+            self.read_u128()
+        } else {
+            Ok(self.read_u64()? as u128)
+        }
+    }
+
+    fn read_addr(&mut self) -> ParseResult<u64> {
+        if self.is_64bit() {
+            self.read_u64()
+        } else {
+            Ok(self.read_u32()? as u64)
+        }
+    }
+
+    fn read_size(&mut self) -> ParseResult<usize> {
+        if self.is_64bit() {
+            Ok(self.read_u64()? as usize)
+        } else {
+            Ok(self.read_u32()? as usize)
+        }
+    }
+
+    fn read_u16_array(&mut self, count: usize) -> ParseResult<Vec<u16>> {
+        read_values(self, count, u16::from_be_bytes, u16::from_le_bytes)
+    }
+
+    fn read_u32_array(&mut self, count: usize) -> ParseResult<Vec<u32>> {
+        read_values(self, count, u32::from_be_bytes, u32::from_le_bytes)
+    }
+
+    fn read_u64_array(&mut self, count: usize) -> ParseResult<Vec<u64>> {
+        read_values(self, count, u64::from_be_bytes, u64::from_le_bytes)
+    }
+
+    fn read_i16_array(&mut self, count: usize) -> ParseResult<Vec<i16>> {
+        read_values(self, count, i16::from_be_bytes, i16::from_le_bytes)
+    }
+
+    fn read_i32_array(&mut self, count: usize) -> ParseResult<Vec<i32>> {
+        read_values(self, count, i32::from_be_bytes, i32::from_le_bytes)
+    }
+
+    fn read_i64_array(&mut self, count: usize) -> ParseResult<Vec<i64>> {
+        read_values(self, count, i64::from_be_bytes, i64::from_le_bytes)
+    }
+
+    fn read_f32_array(&mut self, count: usize) -> ParseResult<Vec<f32>> {
+        read_values(self, count, f32::from_be_bytes, f32::from_le_bytes)
+    }
+}
+
+/// Reads `count` values of `N` bytes with a single bounds check and read,
+/// converting them in place instead of going through one read per value.
+fn read_values<'a, R, T, const N: usize>(
+    reader: &mut R,
+    count: usize,
+    from_be: fn([u8; N]) -> T,
+    from_le: fn([u8; N]) -> T,
+) -> ParseResult<Vec<T>>
+where
+    R: BinaryRead<'a> + ?Sized,
+{
+    reader.check_count::<T>(count)?;
+    let size = count
+        .checked_mul(N)
+        .ok_or_else(|| reader.eof_error(reader.offset(), usize::MAX))?;
+    let bytes = reader.read_n_bytes(size)?;
+    let convert = match reader.endian() {
+        Endian::Big => from_be,
+        Endian::Little => from_le,
+    };
+    Ok(bytes
+        .chunks_exact(N)
+        .map(|chunk| convert(chunk.try_into().unwrap()))
+        .collect())
+}
@@ -1,168 +1,207 @@
-use super::super::utils::concat_number;
-use super::Endian;
+use super::{BinaryRead, Diagnostics, Endian, Hexdump, ParseLimits, ParseResult, Span};
+use std::borrow::Cow;
+use std::ops::Range;
 
 #[derive(PartialEq, Debug, Clone)]
-pub struct BinaryReader {
-    pub data: Vec<u8>,
+pub struct BinaryReader<'a> {
+    pub data: &'a [u8],
     pub offset: usize,
 
     pub endian: Endian,
     pub is_64bit: bool,
+
+    pub path: Vec<String>,
+    pub diagnostics: Diagnostics,
+    pub limits: ParseLimits,
+    pub spans: Option<Vec<Span>>,
+
+    /// Absolute position of `data[0]` when this reader is a window into a
+    /// larger buffer, see [`BinaryReader::sub_reader`].
+    pub base: usize,
 }
 
-impl Default for BinaryReader {
+impl Default for BinaryReader<'_> {
     fn default() -> Self {
         Self {
-            data: Vec::new(),
+            data: &[],
             offset: 0,
             endian: Endian::Little,
             is_64bit: false,
+            path: Vec::new(),
+            diagnostics: Diagnostics::default(),
+            limits: ParseLimits::default(),
+            spans: None,
+            base: 0,
         }
     }
 }
 
-impl BinaryReader {
-    pub fn new(data: Vec<u8>) -> BinaryReader {
+impl<'a> BinaryReader<'a> {
+    /// Creates a reader borrowing `data`, which may be any byte buffer such
+    /// as a `Vec<u8>` or a memory-mapped file. Byte reads return subslices of
+    /// it instead of copies.
+    pub fn new(data: &'a [u8]) -> BinaryReader<'a> {
         BinaryReader {
             data,
             offset: 0,
             endian: Endian::Big,
             is_64bit: false,
+            path: Vec::new(),
+            diagnostics: Diagnostics::default(),
+            limits: ParseLimits::default(),
+            spans: None,
+            base: 0,
         }
     }
+}
 
-    pub fn _concat<T>(&self, high: T, low: T, shift: u8) -> u128
-    where
-        T: num::ToPrimitive,
-        T: std::ops::Shr<usize, Output = T>,
-        T: num::Unsigned,
-    {
-        if self.endian == Endian::Big {
-            concat_number(high, low, shift)
-        } else {
-            concat_number(low, high, shift)
-        }
+impl<'a> BinaryReader<'a> {
+    pub fn read(&mut self, pos: usize, size: usize) -> ParseResult<&'a [u8]> {
+        self.check(pos, size)?;
+        self.record_span(pos, size);
+        Ok(&self.data[pos..pos + size])
     }
 
-    pub fn seek(&mut self, offset: usize) {
-        self.offset = offset;
+    pub fn read_n_bytes(&mut self, size: usize) -> ParseResult<&'a [u8]> {
+        let result = self.read(self.offset, size)?;
+        self.offset += size;
+        Ok(result)
+    }
+
+    /// Returns a reader bounded to `len` bytes at `offset`, sharing this
+    /// reader's modes and parser path. Offsets inside the window are local to
+    /// it and reads past its end fail instead of spilling into neighbouring
+    /// data; errors and spans still use absolute positions. The window starts
+    /// with no diagnostics or spans of its own, see [`Diagnostics::append`].
+    pub fn sub_reader(&self, offset: usize, len: usize) -> ParseResult<BinaryReader<'a>> {
+        self.check(offset, len)?;
+        Ok(BinaryReader {
+            data: &self.data[offset..offset + len],
+            offset: 0,
+            endian: self.endian,
+            is_64bit: self.is_64bit,
+            path: self.path.clone(),
+            diagnostics: Diagnostics::new(self.diagnostics.mode),
+            limits: self.limits,
+            spans: self.spans.as_ref().map(|_| Vec::new()),
+            base: self.base + offset,
+        })
+    }
+
+    /// Like [`BinaryReader::sub_reader`] for the next `len` bytes, moving
+    /// past them.
+    pub fn read_sub_reader(&mut self, len: usize) -> ParseResult<BinaryReader<'a>> {
+        let ret = self.sub_reader(self.offset, len)?;
+        self.offset += len;
+        Ok(ret)
+    }
+
+    pub fn absolute_offset(&self) -> usize {
+        self.base + self.offset
+    }
+
+    /// Dumps the bytes in `range`, clamped to the input, highlighting the
+    /// recorded spans that touch it.
+    pub fn hexdump(&self, range: Range<usize>) -> Hexdump<'a> {
+        let end = range.end.min(self.data.len());
+        let start = range.start.min(end);
+        Hexdump::new(&self.data[start..end])
+            .with_base(self.base + start)
+            .highlight_spans(self.spans.as_deref().unwrap_or_default())
     }
 }
 
-impl BinaryReader {
-    pub fn peek_u8(&self) -> u8 {
-        self.data[self.offset]
+impl<'a> BinaryRead<'a> for BinaryReader<'a> {
+    fn offset(&self) -> usize {
+        self.offset
     }
 
-    pub fn read_u8(&mut self) -> u8 {
-        let result = self.data[self.offset];
-        self.offset += 1;
-        result
+    fn seek(&mut self, offset: usize) {
+        self.offset = offset;
     }
 
-    pub fn read_u16(&mut self) -> u16 {
-        let high = self.read_u8() as u16;
-        let low = self.read_u8() as u16;
-        self._concat(high, low, 8) as u16
+    fn endian(&self) -> Endian {
+        self.endian
     }
 
-    pub fn read_u32(&mut self) -> u32 {
-        let high = self.read_u16() as u32;
-        let low = self.read_u16() as u32;
-        self._concat(high, low, 16) as u32
+    fn set_endian(&mut self, endian: Endian) {
+        self.endian = endian;
     }
 
-    pub fn read_u64(&mut self) -> u64 {
-        let high = self.read_u32() as u64;
-        let low = self.read_u32() as u64;
-        self._concat(high, low, 32) as u64
+    fn is_64bit(&self) -> bool {
+        self.is_64bit
     }
 
-    pub fn read_u128(&mut self) -> u128 {
-        let high = self.read_u64() as u128;
-        let low = self.read_u64() as u128;
-        self._concat(high, low, 64) as u128
+    fn set_64bit(&mut self, is_64bit: bool) {
+        self.is_64bit = is_64bit;
     }
-}
 
-impl BinaryReader {
-    pub fn read_half(&mut self) -> u32 {
-        if self.is_64bit {
-            self.read_u32()
-        } else {
-            self.read_u16() as u32
-        }
+    fn path(&self) -> &[String] {
+        &self.path
     }
 
-    pub fn read_word(&mut self) -> u64 {
-        if self.is_64bit {
-            self.read_u64()
-        } else {
-            self.read_u32() as u64
-        }
+    fn path_mut(&mut self) -> &mut Vec<String> {
+        &mut self.path
     }
 
-    pub fn read_dword(&mut self) -> u128 {
-        if self.is_64bit {
-            self.read_u128()
-        } else {
-            self.read_u64() as u128
-        }
+    fn diagnostics(&self) -> &Diagnostics {
+        &self.diagnostics
     }
 
-    pub fn read_addr(&mut self) -> u64 {
-        if self.is_64bit {
-            self.read_u64()
-        } else {
-            self.read_u32() as u64
-        }
+    fn diagnostics_mut(&mut self) -> &mut Diagnostics {
+        &mut self.diagnostics
     }
 
-    pub fn read_size(&mut self) -> usize {
-        if self.is_64bit {
-            self.read_u64() as usize
-        } else {
-            self.read_u32() as usize
-        }
+    fn limits(&self) -> &ParseLimits {
+        &self.limits
     }
-}
 
-impl BinaryReader {
-    pub fn read(&mut self, pos: usize, size: usize) -> Vec<u8> {
-        self.data[pos..pos + size].to_vec()
+    fn spans(&self) -> Option<&[Span]> {
+        self.spans.as_deref()
     }
 
-    pub fn read_n_bytes(&mut self, size: usize) -> Vec<u8> {
-        let mut result = Vec::new();
-        for _ in 0..size {
-            result.push(self.read_u8());
-        }
-        result
+    fn spans_mut(&mut self) -> &mut Option<Vec<Span>> {
+        &mut self.spans
+    }
+
+    fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    fn base(&self) -> usize {
+        self.base
+    }
+
+    fn read_at(&mut self, pos: usize, size: usize) -> ParseResult<Cow<'a, [u8]>> {
+        self.check(pos, size)?;
+        Ok(Cow::Borrowed(&self.data[pos..pos + size]))
     }
 
-    pub fn read_u16_string(&mut self) -> String {
-        let length = self.read_u16() as usize;
-        let bytes = self.read_n_bytes(length);
-        String::from_utf8_lossy(&bytes).to_string()
+    fn read_array<const N: usize>(&mut self) -> ParseResult<[u8; N]> {
+        let bytes = self.read_n_bytes(N)?;
+        Ok(bytes.try_into().unwrap())
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use super::BinaryRead;
+    use crate::binary_reader::ParseErrorKind;
+
     #[test]
     fn test_big_endian_read() {
         let data = vec![0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08];
-        let mut reader = super::BinaryReader::new(data);
+        let mut reader = super::BinaryReader::new(&data);
         reader.endian = super::Endian::Big;
-        assert_eq!(reader.read_u64(), 0x0102030405060708);
+        assert_eq!(reader.read_u64().unwrap(), 0x0102030405060708);
     }
 
     #[test]
     fn test_little_endian_read() {
         let data = vec![0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08];
-        let mut reader = super::BinaryReader::new(data);
+        let mut reader = super::BinaryReader::new(&data);
         reader.endian = super::Endian::Little;
-        assert_eq!(reader.read_u64(), 0x0807060504030201);
+        assert_eq!(reader.read_u64().unwrap(), 0x0807060504030201);
     }
 
     #[test]
@@ -171,68 +210,71 @@ mod tests {
             0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x0a, 0x0b, 0x0c, 0x0d, 0x0e,
             0x0f, 0x10,
         ];
-        let mut reader = super::BinaryReader::new(data);
-        assert_eq!(reader.read_u128(), 0x0001020304050607080a0b0c0d0e0f10);
+        let mut reader = super::BinaryReader::new(&data);
+        assert_eq!(
+            reader.read_u128().unwrap(),
+            0x0001020304050607080a0b0c0d0e0f10
+        );
     }
 
     #[test]
     fn test_u64() {
         let data = vec![0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08];
-        let mut reader = super::BinaryReader::new(data);
-        assert_eq!(reader.read_u64(), 0x0102030405060708);
+        let mut reader = super::BinaryReader::new(&data);
+        assert_eq!(reader.read_u64().unwrap(), 0x0102030405060708);
     }
 
     #[test]
     fn test_u32() {
         let data = vec![0x01, 0x02, 0x03, 0x04];
-        let mut reader = super::BinaryReader::new(data);
-        assert_eq!(reader.read_u32(), 0x01020304);
+        let mut reader = super::BinaryReader::new(&data);
+        assert_eq!(reader.read_u32().unwrap(), 0x01020304);
     }
 
     #[test]
     fn test_u16() {
         let data = vec![0x01, 0x02];
-        let mut reader = super::BinaryReader::new(data);
-        assert_eq!(reader.read_u16(), 0x0102);
+        let mut reader = super::BinaryReader::new(&data);
+        assert_eq!(reader.read_u16().unwrap(), 0x0102);
     }
 
     #[test]
     fn test_u8() {
         let data = vec![0x01];
-        let mut reader = super::BinaryReader::new(data);
-        assert_eq!(reader.read_u8(), 0x01);
+        let mut reader = super::BinaryReader::new(&data);
+        assert_eq!(reader.read_u8().unwrap(), 0x01);
     }
 
     #[test]
     fn test_half_64bit() {
         let data = vec![0x01, 0x02, 0x03, 0x04];
-        let mut reader = super::BinaryReader::new(data);
+        let mut reader = super::BinaryReader::new(&data);
         reader.is_64bit = true;
-        assert_eq!(reader.read_half(), 0x01020304);
+        assert_eq!(reader.read_half().unwrap(), 0x01020304);
     }
 
     #[test]
     fn test_half_32bit() {
         let data = vec![0x01, 0x02];
-        let mut reader = super::BinaryReader::new(data);
+        let mut reader = super::BinaryReader::new(&data);
         reader.is_64bit = false;
-        assert_eq!(reader.read_half(), 0x0102);
+        assert_eq!(reader.read_half().unwrap(), 0x0102);
     }
 
     #[test]
     fn test_word_64bit() {
         let data = vec![0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08];
-        let mut reader = super::BinaryReader::new(data);
+        let mut reader = super::BinaryReader::new(&data);
         reader.is_64bit = true;
-        assert_eq!(reader.read_word(), 0x0102030405060708);
+        assert_eq!(reader.read_word().unwrap(), 0x0102030405060708);
     }
 
     #[test]
     fn test_word_32bit() {
         let data = vec![0x01, 0x02, 0x03, 0x04];
-        let mut reader = super::BinaryReader::new(data);
+        let mut reader = super::BinaryReader::new(&data);
         reader.is_64bit = false;
-        assert_eq!(reader.read_word(), 0x01020304);
+        assert_eq!(reader.read_word().unwrap(), 0x01020304);
     }
 
     #[test]
@@ -241,50 +283,167 @@ mod tests {
             0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b, 0x0c, 0x0d, 0x0e,
             0x0f, 0x10,
         ];
-        let mut reader = super::BinaryReader::new(data);
+        let mut reader = super::BinaryReader::new(&data);
         reader.is_64bit = true;
-        assert_eq!(reader.read_dword(), 0x0102030405060708090a0b0c0d0e0f10);
+        assert_eq!(
+            reader.read_dword().unwrap(),
+            0x0102030405060708090a0b0c0d0e0f10
+        );
     }
 
     #[test]
     fn test_dword_32bit() {
         let data = vec![0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08];
-        let mut reader = super::BinaryReader::new(data);
+        let mut reader = super::BinaryReader::new(&data);
         reader.is_64bit = false;
-        assert_eq!(reader.read_dword(), 0x0102030405060708);
+        assert_eq!(reader.read_dword().unwrap(), 0x0102030405060708);
     }
 
     #[test]
     fn test_read_n_bytes() {
         let data = vec![0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08];
-        let mut reader = super::BinaryReader::new(data);
-        assert_eq!(reader.read_n_bytes(4), vec![0x01, 0x02, 0x03, 0x04]);
+        let mut reader = super::BinaryReader::new(&data);
+        assert_eq!(
+            reader.read_n_bytes(4).unwrap(),
+            vec![0x01, 0x02, 0x03, 0x04]
+        );
     }
 
     #[test]
     fn test_read() {
         let data = vec![0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08];
-        let mut reader = super::BinaryReader::new(data);
-        assert_eq!(reader.read(4, 2), vec![0x05, 0x06]);
+        let mut reader = super::BinaryReader::new(&data);
+        assert_eq!(reader.read(4, 2).unwrap(), [0x05, 0x06]);
     }
 
     #[test]
     fn test_read_all() {
         let data = vec![0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08];
-        let mut reader = super::BinaryReader::new(data);
+        let mut reader = super::BinaryReader::new(&data);
         assert_eq!(
-            reader.read(0, 8),
-            vec![0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08]
+            reader.read(0, 8).unwrap(),
+            [0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08]
         );
     }
 
     #[test]
     fn test_peek_u8() {
         let data = vec![0x01, 0x02];
-        let mut reader = super::BinaryReader::new(data);
-        assert_eq!(reader.peek_u8(), 0x01);
-        assert_eq!(reader.read_u8(), 0x01);
-        assert_eq!(reader.peek_u8(), 0x02);
-        assert_eq!(reader.read_u8(), 0x02);
+        let mut reader = super::BinaryReader::new(&data);
+        assert_eq!(reader.peek_u8().unwrap(), 0x01);
+        assert_eq!(reader.read_u8().unwrap(), 0x01);
+        assert_eq!(reader.peek_u8().unwrap(), 0x02);
+        assert_eq!(reader.read_u8().unwrap(), 0x02);
+    }
+
+    #[test]
+    fn test_eof_error() {
+        let data = vec![0x01, 0x02, 0x03];
+        let mut reader = super::BinaryReader::new(&data);
+        reader.read_u8().unwrap();
+        let error = reader.read_u32().unwrap_err();
+        assert_eq!(error.kind, ParseErrorKind::UnexpectedEof { available: 2 });
+        assert_eq!(error.offset, 1);
+        assert_eq!(error.length, 4);
+        assert_eq!(reader.offset, 1);
+    }
+
+    #[test]
+    fn test_read_out_of_range() {
+        let data = vec![0x01, 0x02, 0x03, 0x04];
+        let mut reader = super::BinaryReader::new(&data);
+        let error = reader.read(2, usize::MAX).unwrap_err();
+        assert_eq!(error.offset, 2);
+        assert_eq!(error.length, usize::MAX);
+    }
+
+    #[test]
+    fn test_scoped_path() {
+        let mut reader = super::BinaryReader::new(&[]);
+        let error = reader
+            .scoped("Outer", |r| r.scoped("Inner[1]", |r| r.read_u16()))
+            .unwrap_err();
+        assert_eq!(error.path_string(), "Outer > Inner[1]");
+        assert!(reader.path.is_empty());
+    }
+
+    #[test]
+    fn test_read_is_zero_copy() {
+        let data = vec![0x01, 0x02, 0x03, 0x04];
+        let mut reader = super::BinaryReader::new(&data);
+        reader.read_u8().unwrap();
+        let bytes = reader.read_n_bytes(2).unwrap();
+        assert_eq!(bytes.as_ptr(), data[1..].as_ptr());
+        assert_eq!(reader.offset, 3);
+    }
+
+    #[test]
+    fn test_sub_reader() {
+        let data = vec![0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08];
+        let mut reader = super::BinaryReader::new(&data);
+        reader.endian = super::Endian::Little;
+        reader.read_u16().unwrap();
+
+        let mut window = reader.read_sub_reader(4).unwrap();
+        assert_eq!(reader.offset, 6);
+        assert_eq!(window.endian, super::Endian::Little);
+        assert_eq!(window.read_u16().unwrap(), 0x0403);
+        assert_eq!(window.offset, 2);
+        assert_eq!(window.absolute_offset(), 4);
+
+        let mut nested = window.sub_reader(1, 3).unwrap();
+        assert_eq!(nested.base, 3);
+        assert_eq!(nested.read_u8().unwrap(), 0x04);
+        assert!(window.sub_reader(2, 3).is_err());
+    }
+
+    #[test]
+    fn test_sub_reader_bounds() {
+        let data = vec![0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08];
+        let reader = super::BinaryReader::new(&data);
+        let mut window = reader.sub_reader(2, 3).unwrap();
+        window.read_u16().unwrap();
+
+        let error = window.read_u16().unwrap_err();
+        assert_eq!(error.kind, ParseErrorKind::UnexpectedEof { available: 1 });
+        assert_eq!(error.offset, 4);
+        assert_eq!(error.length, 2);
+    }
+
+    #[test]
+    fn test_coverage() {
+        let data = vec![0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08];
+        let mut reader = super::BinaryReader::new(&data);
+        assert!(reader.coverage().is_none());
+
+        reader.record_spans();
+        reader.read_u16().unwrap();
+        reader.peek_u8().unwrap();
+        reader.scoped("tail", |reader| reader.read(6, 2)).unwrap();
+
+        let spans = reader.spans.as_ref().unwrap();
+        assert_eq!(spans.len(), 2);
+        assert_eq!(spans[1].label(), "tail");
+
+        let report = reader.coverage().unwrap();
+        assert_eq!(report.covered, 4);
+        assert_eq!(report.gaps, vec![2..6]);
+        assert_eq!(report.percent(), 50.0);
+    }
+
+    #[test]
+    fn test_read_arrays() {
+        let data = vec![0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08];
+        let mut reader = super::BinaryReader::new(&data);
+        assert_eq!(reader.read_u16_array(2).unwrap(), vec![0x0102, 0x0304]);
+        reader.endian = super::Endian::Little;
+        assert_eq!(reader.read_u32_array(1).unwrap(), vec![0x08070605]);
+        assert_eq!(reader.read_u32_array(0).unwrap(), vec![]);
+
+        reader.seek(0);
+        let error = reader.read_u64_array(2).unwrap_err();
+        assert_eq!(error.kind, ParseErrorKind::UnexpectedEof { available: 8 });
+        assert_eq!(error.length, 16);
+        assert!(reader.read_i32_array(usize::MAX).is_err());
     }
 }
@@ -0,0 +1,101 @@
+use super::{BinaryRead, ParseResult};
+
+/// Which bit of each byte comes first in a packed stream.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum BitOrder {
+    MsbFirst,
+    LsbFirst,
+}
+
+/// Reads values narrower than a byte from an underlying reader, pulling in
+/// whole bytes as they are needed.
+pub struct BitReader<'r, R> {
+    reader: &'r mut R,
+    order: BitOrder,
+    current: u8,
+    remaining: u32,
+}
+
+impl<'r, 'a, R: BinaryRead<'a>> BitReader<'r, R> {
+    pub fn new(reader: &'r mut R, order: BitOrder) -> BitReader<'r, R> {
+        BitReader {
+            reader,
+            order,
+            current: 0,
+            remaining: 0,
+        }
+    }
+
+    pub fn read_bit(&mut self) -> ParseResult<bool> {
+        if self.remaining == 0 {
+            self.current = self.reader.read_u8()?;
+            self.remaining = 8;
+        }
+        self.remaining -= 1;
+        let shift = match self.order {
+            BitOrder::MsbFirst => self.remaining,
+            BitOrder::LsbFirst => 7 - self.remaining,
+        };
+        Ok(self.current >> shift & 1 != 0)
+    }
+
+    /// Reads a `count`-bit value. In MSB-first order the first bit read is
+    /// the most significant one, in LSB-first order the least significant.
+    pub fn read_bits(&mut self, count: u32) -> ParseResult<u64> {
+        if count > 64 {
+            return Err(self
+                .reader
+                .invalid_data(format!("cannot read {} bits at once", count)));
+        }
+        let mut ret = 0;
+        for i in 0..count {
+            let bit = self.read_bit()? as u64;
+            match self.order {
+                BitOrder::MsbFirst => ret = ret << 1 | bit,
+                BitOrder::LsbFirst => ret |= bit << i,
+            }
+        }
+        Ok(ret)
+    }
+
+    /// Skips the rest of the current byte.
+    pub fn align(&mut self) {
+        self.remaining = 0;
+    }
+
+    pub fn reader(&mut self) -> &mut R {
+        self.reader
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BitOrder, BitReader};
+    use crate::binary_reader::{BinaryReader, ParseErrorKind};
+
+    #[test]
+    fn test_msb_first() {
+        let data = vec![0b1011_0010, 0b0111_1111];
+        let mut reader = BinaryReader::new(&data);
+        let mut bits = BitReader::new(&mut reader, BitOrder::MsbFirst);
+        assert!(bits.read_bit().unwrap());
+        assert_eq!(bits.read_bits(3).unwrap(), 0b011);
+        assert_eq!(bits.read_bits(6).unwrap(), 0b00_1001);
+        bits.align();
+        assert!(bits.read_bit().is_err());
+    }
+
+    #[test]
+    fn test_lsb_first() {
+        let data = vec![0b1011_0010, 0b0111_1111];
+        let mut reader = BinaryReader::new(&data);
+        let mut bits = BitReader::new(&mut reader, BitOrder::LsbFirst);
+        assert!(!bits.read_bit().unwrap());
+        assert_eq!(bits.read_bits(3).unwrap(), 0b001);
+        assert_eq!(bits.read_bits(6).unwrap(), 0b11_1011);
+        assert_eq!(bits.reader().offset, 2);
+
+        let error = bits.read_bits(65).unwrap_err();
+        assert!(matches!(error.kind, ParseErrorKind::InvalidData(_)));
+    }
+}
@@ -0,0 +1,166 @@
+use std::ops::Range;
+
+/// Bytes consumed by one read while span recording is on, see
+/// [`BinaryRead::record_spans`](super::BinaryRead::record_spans).
+#[derive(PartialEq, Debug, Clone)]
+pub struct Span {
+    pub offset: usize,
+    pub length: usize,
+    pub path: Vec<String>,
+}
+
+impl Span {
+    pub fn end(&self) -> usize {
+        self.offset + self.length
+    }
+
+    pub fn label(&self) -> String {
+        self.path.join(" > ")
+    }
+}
+
+/// Bytes explained by two different reads.
+#[derive(PartialEq, Debug, Clone)]
+pub struct Overlap {
+    pub range: Range<usize>,
+    pub first: String,
+    pub second: String,
+}
+
+/// Which bytes of an input the recorded spans explain.
+///
+/// Reading the same span twice, e.g. a header that is parsed again, is not
+/// reported as an overlap.
+#[derive(PartialEq, Debug, Clone)]
+pub struct CoverageReport {
+    pub range: Range<usize>,
+    pub covered: usize,
+    pub gaps: Vec<Range<usize>>,
+    pub overlaps: Vec<Overlap>,
+}
+
+impl CoverageReport {
+    pub fn new(range: Range<usize>, spans: &[Span]) -> CoverageReport {
+        let mut spans = spans
+            .iter()
+            .filter(|span| span.length > 0)
+            .collect::<Vec<_>>();
+        spans.sort_by_key(|span| (span.offset, span.length));
+        spans.dedup_by(|a, b| a.offset == b.offset && a.length == b.length);
+
+        let mut ret = CoverageReport {
+            range: range.clone(),
+            covered: 0,
+            gaps: Vec::new(),
+            overlaps: Vec::new(),
+        };
+
+        let mut cursor = range.start;
+        let mut owner: Option<&Span> = None;
+        for span in spans {
+            let start = span.offset.max(range.start);
+            let end = span.end().min(range.end);
+            if start >= end {
+                continue;
+            }
+
+            if start > cursor {
+                ret.gaps.push(cursor..start);
+            } else if let Some(owner) = owner.filter(|_| start < cursor) {
+                ret.overlaps.push(Overlap {
+                    range: start..end.min(cursor),
+                    first: owner.label(),
+                    second: span.label(),
+                });
+            }
+
+            if end > cursor {
+                ret.covered += end - start.max(cursor);
+                cursor = end;
+                owner = Some(span);
+            }
+        }
+        if cursor < range.end {
+            ret.gaps.push(cursor..range.end);
+        }
+
+        ret
+    }
+
+    pub fn len(&self) -> usize {
+        self.range.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.range.is_empty()
+    }
+
+    pub fn percent(&self) -> f64 {
+        if self.is_empty() {
+            100.0
+        } else {
+            self.covered as f64 * 100.0 / self.len() as f64
+        }
+    }
+}
+
+impl std::fmt::Display for CoverageReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        writeln!(
+            f,
+            "{:#x} of {:#x} bytes explained ({:.2}%)",
+            self.covered,
+            self.len(),
+            self.percent()
+        )?;
+        for gap in &self.gaps {
+            writeln!(
+                f,
+                "gap {:#010x}..{:#010x} ({:#x} bytes)",
+                gap.start,
+                gap.end,
+                gap.len()
+            )?;
+        }
+        for overlap in &self.overlaps {
+            writeln!(
+                f,
+                "overlap {:#010x}..{:#010x} between {} and {}",
+                overlap.range.start, overlap.range.end, overlap.first, overlap.second
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CoverageReport, Span};
+
+    fn span(offset: usize, length: usize, label: &str) -> Span {
+        Span {
+            offset,
+            length,
+            path: vec![label.to_string()],
+        }
+    }
+
+    #[test]
+    fn test_report() {
+        let spans = vec![
+            span(0x10, 4, "c"),
+            span(0, 4, "a"),
+            span(2, 4, "b"),
+            span(0, 4, "a"),
+        ];
+        let report = CoverageReport::new(0..0x20, &spans);
+
+        assert_eq!(report.covered, 10);
+        assert_eq!(report.gaps, vec![6..0x10, 0x14..0x20]);
+        assert_eq!(report.overlaps.len(), 1);
+        assert_eq!(report.overlaps[0].range, 2..4);
+        assert_eq!(report.overlaps[0].first, "a");
+        assert_eq!(report.overlaps[0].second, "b");
+        assert_eq!(report.percent(), 31.25);
+    }
+}
@@ -0,0 +1,103 @@
+#[derive(PartialEq, Eq, PartialOrd, Ord, Debug, Clone, Copy)]
+pub enum Severity {
+    Info,
+    Warning,
+}
+
+impl std::fmt::Display for Severity {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Severity::Info => write!(f, "info"),
+            Severity::Warning => write!(f, "warning"),
+        }
+    }
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum DiagnosticCode {
+    BadElfMagic,
+    BadElfVersion,
+    UnknownElfClass,
+    UnknownElfEncoding,
+    ElfLayout,
+    /// A secondary table such as the imports of an RPX could not be decoded
+    /// and was skipped.
+    BadSectionContents,
+}
+
+impl std::fmt::Display for DiagnosticCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        std::fmt::Debug::fmt(self, f)
+    }
+}
+
+/// Something noteworthy but recoverable found while parsing.
+#[derive(PartialEq, Debug, Clone)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub code: DiagnosticCode,
+    pub offset: usize,
+    pub length: usize,
+    pub message: String,
+    pub path: Vec<String>,
+}
+
+impl std::fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "{} {}: {} at {:#010x}",
+            self.severity, self.code, self.message, self.offset
+        )?;
+        if !self.path.is_empty() {
+            write!(f, " in {}", self.path.join(" > "))?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Default)]
+pub enum DiagnosticMode {
+    /// Keep every diagnostic for the caller to inspect.
+    #[default]
+    Collect,
+    /// Fail the parse on the first warning; info is still collected.
+    Strict,
+    /// Drop everything.
+    Ignore,
+}
+
+/// Diagnostics gathered by a reader, see [`BinaryRead::report_at`].
+///
+/// [`BinaryRead::report_at`]: super::BinaryRead::report_at
+#[derive(PartialEq, Debug, Clone, Default)]
+pub struct Diagnostics {
+    pub mode: DiagnosticMode,
+    pub entries: Vec<Diagnostic>,
+}
+
+impl Diagnostics {
+    pub fn new(mode: DiagnosticMode) -> Diagnostics {
+        Diagnostics {
+            mode,
+            entries: Vec::new(),
+        }
+    }
+
+    pub fn warnings(&self) -> impl Iterator<Item = &Diagnostic> {
+        self.entries
+            .iter()
+            .filter(|diagnostic| diagnostic.severity >= Severity::Warning)
+    }
+
+    pub fn contains(&self, code: DiagnosticCode) -> bool {
+        self.entries
+            .iter()
+            .any(|diagnostic| diagnostic.code == code)
+    }
+
+    /// Moves the entries of `other`, e.g. from a sub-reader, into `self`.
+    pub fn append(&mut self, other: &mut Diagnostics) {
+        self.entries.append(&mut other.entries);
+    }
+}
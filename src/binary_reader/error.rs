@@ -0,0 +1,87 @@
+use super::{DiagnosticCode, Limit};
+
+#[derive(PartialEq, Debug, Clone)]
+pub enum ParseErrorKind {
+    UnexpectedEof {
+        available: usize,
+    },
+    InvalidData(String),
+    Decompress(String),
+    Io(String),
+    /// A warning promoted to an error by [`DiagnosticMode::Strict`].
+    ///
+    /// [`DiagnosticMode::Strict`]: super::DiagnosticMode::Strict
+    Diagnostic(DiagnosticCode, String),
+    /// A pointer whose address does not map into the input.
+    BadPointer(u64),
+    /// A pointer back into a structure that is still being read.
+    PointerCycle(u64),
+    LimitExceeded {
+        limit: Limit,
+        requested: usize,
+        max: usize,
+    },
+}
+
+impl std::fmt::Display for ParseErrorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            ParseErrorKind::UnexpectedEof { available } => {
+                write!(f, "unexpected end of data ({} bytes available)", available)
+            }
+            ParseErrorKind::InvalidData(message) => write!(f, "invalid data: {}", message),
+            ParseErrorKind::Decompress(message) => write!(f, "decompression failed: {}", message),
+            ParseErrorKind::Io(message) => write!(f, "I/O error: {}", message),
+            ParseErrorKind::Diagnostic(code, message) => write!(f, "{}: {}", code, message),
+            ParseErrorKind::BadPointer(address) => {
+                write!(f, "pointer {:#x} does not map into the input", address)
+            }
+            ParseErrorKind::PointerCycle(address) => {
+                write!(f, "pointer {:#x} leads back into itself", address)
+            }
+            ParseErrorKind::LimitExceeded {
+                limit,
+                requested,
+                max,
+            } => write!(f, "{} {} exceeds the limit of {}", limit, requested, max),
+        }
+    }
+}
+
+/// Error raised by every fallible read, carrying where it happened.
+///
+/// `offset` and `length` describe the byte range that was requested, with
+/// `offset` absolute even when it was read through a sub-reader window, and
+/// `path` is the breadcrumb of parsers that were active at that moment,
+/// e.g. `["Rpx", "SectionHeader[7]", "data"]`.
+#[derive(PartialEq, Debug, Clone)]
+pub struct ParseError {
+    pub kind: ParseErrorKind,
+    pub offset: usize,
+    pub length: usize,
+    pub path: Vec<String>,
+}
+
+impl ParseError {
+    pub fn path_string(&self) -> String {
+        self.path.join(" > ")
+    }
+}
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "{} at {:#010x} (length {:#x})",
+            self.kind, self.offset, self.length
+        )?;
+        if !self.path.is_empty() {
+            write!(f, " in {}", self.path_string())?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+pub type ParseResult<T> = Result<T, ParseError>;
@@ -0,0 +1,208 @@
+use super::Span;
+use std::ops::Range;
+
+const ROW: usize = 16;
+const HEX_WIDTH: usize = ROW * 2 + ROW / 4;
+
+/// A labelled byte range, in the same offsets as the dump.
+#[derive(PartialEq, Debug, Clone)]
+pub struct Highlight {
+    pub range: Range<usize>,
+    pub label: String,
+}
+
+/// Renders bytes as `offset: hex  ascii` rows like `_hexdump` in
+/// `lib/migrated/binary.py`. Every highlight that touches a row is drawn
+/// underneath it as a line of `^` markers followed by its label.
+///
+/// ```text
+/// 00000000: 7f454c46 01020100 00000000 00000000  .ELF............
+///           ^^^^^^^^                             magic
+/// ```
+#[derive(PartialEq, Debug, Clone)]
+pub struct Hexdump<'d> {
+    data: &'d [u8],
+    base: usize,
+    highlights: Vec<Highlight>,
+    utf16: bool,
+}
+
+impl<'d> Hexdump<'d> {
+    pub fn new(data: &'d [u8]) -> Hexdump<'d> {
+        Hexdump {
+            data,
+            base: 0,
+            highlights: Vec::new(),
+            utf16: false,
+        }
+    }
+
+    /// Sets the offset printed for `data[0]`, which is also the origin of
+    /// highlight ranges.
+    pub fn with_base(mut self, base: usize) -> Hexdump<'d> {
+        self.base = base;
+        self
+    }
+
+    pub fn highlight<S: Into<String>>(mut self, range: Range<usize>, label: S) -> Hexdump<'d> {
+        self.highlights.push(Highlight {
+            range,
+            label: label.into(),
+        });
+        self
+    }
+
+    /// Highlights recorded spans, labelled with their parser path.
+    pub fn highlight_spans(mut self, spans: &[Span]) -> Hexdump<'d> {
+        for span in spans {
+            self = self.highlight(span.offset..span.end(), span.label());
+        }
+        self
+    }
+
+    /// Adds a column decoding each pair of bytes of a row as a UTF-16BE code
+    /// unit.
+    pub fn with_utf16(mut self) -> Hexdump<'d> {
+        self.utf16 = true;
+        self
+    }
+
+    fn hex(row: &[u8]) -> String {
+        let mut ret = String::with_capacity(HEX_WIDTH);
+        for (i, byte) in row.iter().enumerate() {
+            ret += &format!("{:02x}", byte);
+            if i % 4 == 3 {
+                ret.push(' ');
+            }
+        }
+        format!("{:<width$}", ret, width = HEX_WIDTH)
+    }
+
+    fn ascii(row: &[u8]) -> String {
+        row.iter()
+            .map(|&byte| {
+                if byte.is_ascii_graphic() || byte == b' ' {
+                    byte as char
+                } else {
+                    '.'
+                }
+            })
+            .collect()
+    }
+
+    fn utf16(row: &[u8]) -> String {
+        row.chunks(2)
+            .map(|pair| match pair {
+                [high, low] => char::from_u32(u16::from_be_bytes([*high, *low]) as u32)
+                    .filter(|c| !c.is_control())
+                    .unwrap_or('.'),
+                _ => '.',
+            })
+            .collect()
+    }
+
+    fn markers(&self, row: Range<usize>, highlight: &Highlight) -> Option<String> {
+        let start = highlight.range.start.max(row.start);
+        let end = highlight.range.end.min(row.end);
+        if start >= end {
+            return None;
+        }
+        let mut markers = vec![' '; HEX_WIDTH];
+        for i in start - row.start..end - row.start {
+            let column = i * 2 + i / 4;
+            markers[column] = '^';
+            markers[column + 1] = '^';
+        }
+        Some(markers.into_iter().collect())
+    }
+}
+
+impl std::fmt::Display for Hexdump<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        for (i, row) in self.data.chunks(ROW).enumerate() {
+            let start = self.base + i * ROW;
+            let mut line = format!(
+                "{:08x}: {} {}",
+                start,
+                Hexdump::hex(row),
+                Hexdump::ascii(row)
+            );
+            if self.utf16 {
+                line += &format!(
+                    "{:width$}  {}",
+                    "",
+                    Hexdump::utf16(row),
+                    width = ROW - row.len()
+                );
+            }
+            writeln!(f, "{}", line)?;
+
+            for highlight in &self.highlights {
+                if let Some(markers) = self.markers(start..start + row.len(), highlight) {
+                    let line = format!("{:10}{} {}", "", markers, highlight.label);
+                    writeln!(f, "{}", line.trim_end())?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Hexdump;
+    use crate::binary_reader::{BinaryRead, BinaryReader};
+
+    fn markers(hex: &str, label: &str) -> String {
+        format!("{:10}{:<36} {}", "", hex, label)
+    }
+
+    #[test]
+    fn test_rows() {
+        let mut data = b"\x7fELF\x01\x02\x01\x00".to_vec();
+        data.extend_from_slice(&[0; 8]);
+        data.extend_from_slice(b"\x00h\x00i");
+
+        let dump = Hexdump::new(&data)
+            .with_base(0x100)
+            .highlight(0x100..0x104, "magic")
+            .highlight(0x10e..0x112, "name")
+            .to_string();
+        let expected = [
+            "00000100: 7f454c46 01020100 00000000 00000000  .ELF............".to_string(),
+            markers("^^^^^^^^", "magic"),
+            markers("                               ^^^^", "name"),
+            "00000110: 00680069                             .h.i".to_string(),
+            markers("^^^^", "name"),
+        ];
+        assert_eq!(dump.lines().collect::<Vec<_>>(), expected);
+    }
+
+    #[test]
+    fn test_utf16() {
+        let data = b"\x00h\x00i\x30\x42\x00\x00";
+        let dump = Hexdump::new(data).with_utf16().to_string();
+        assert_eq!(
+            dump,
+            format!(
+                "00000000: 00680069 30420000 {:18} .h.i0B..{:10}hi\u{3042}.\n",
+                "", ""
+            )
+        );
+    }
+
+    #[test]
+    fn test_reader_spans() {
+        let data = vec![0x01, 0x02, 0x03, 0x04, 0x05];
+        let mut reader = BinaryReader::new(&data);
+        reader.record_spans();
+        reader.read_u8().unwrap();
+        reader.scoped("value", |reader| reader.read_u16()).unwrap();
+
+        let dump = reader.hexdump(1..5).to_string();
+        let lines = dump.lines().collect::<Vec<_>>();
+        assert_eq!(lines[0], format!("00000001: 02030405 {:27} ....", ""));
+        assert_eq!(lines[1], markers("^^^^", "value"));
+        assert_eq!(lines.len(), 2);
+    }
+}
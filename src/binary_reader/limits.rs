@@ -0,0 +1,57 @@
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum Limit {
+    Allocation,
+    Elements,
+    Depth,
+    DecompressedSize,
+}
+
+impl std::fmt::Display for Limit {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Limit::Allocation => write!(f, "allocation size"),
+            Limit::Elements => write!(f, "element count"),
+            Limit::Depth => write!(f, "nesting depth"),
+            Limit::DecompressedSize => write!(f, "decompressed size"),
+        }
+    }
+}
+
+/// Bounds on what an input may make a parser do, so that sizes read from
+/// corrupt or hostile files fail with
+/// [`ParseErrorKind::LimitExceeded`](super::ParseErrorKind::LimitExceeded)
+/// instead of exhausting memory or the stack.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub struct ParseLimits {
+    /// Bytes a single collection may allocate up front.
+    pub max_allocation: usize,
+    /// Elements in a single collection.
+    pub max_elements: usize,
+    /// Entries in the parser path, which grows with every nested structure.
+    /// The default leaves headroom for unoptimised builds on 2 MiB threads.
+    pub max_depth: usize,
+    /// Bytes produced by inflating a single compressed block.
+    pub max_decompressed_size: usize,
+}
+
+impl ParseLimits {
+    pub fn unlimited() -> ParseLimits {
+        ParseLimits {
+            max_allocation: usize::MAX,
+            max_elements: usize::MAX,
+            max_depth: usize::MAX,
+            max_decompressed_size: usize::MAX,
+        }
+    }
+}
+
+impl Default for ParseLimits {
+    fn default() -> ParseLimits {
+        ParseLimits {
+            max_allocation: 256 << 20,
+            max_elements: 16 << 20,
+            max_depth: 128,
+            max_decompressed_size: 256 << 20,
+        }
+    }
+}
@@ -1,7 +1,27 @@
+mod binary_read;
+#[allow(clippy::module_inception)]
 mod binary_reader;
+mod bit_reader;
+mod coverage;
+mod diagnostics;
 mod endian;
+mod error;
+mod hexdump;
+mod limits;
 mod parseable;
+mod pointer;
+mod stream_reader;
 
+pub use binary_read::BinaryRead;
 pub use binary_reader::BinaryReader;
+pub use bit_reader::{BitOrder, BitReader};
+pub use coverage::{CoverageReport, Overlap, Span};
+pub use diagnostics::{Diagnostic, DiagnosticCode, DiagnosticMode, Diagnostics, Severity};
 pub use endian::Endian;
+pub use error::{ParseError, ParseErrorKind, ParseResult};
+pub use hexdump::{Hexdump, Highlight};
+pub use limits::{Limit, ParseLimits};
 pub use parseable::Parsable;
+pub use pointer::{AddressRange, AddressSpace, Ptr};
+pub use stream_reader::StreamReader;
+pub use wiiu_derive::Parsable;
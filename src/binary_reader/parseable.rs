@@ -1,5 +1,166 @@
-use super::BinaryReader;
+use super::{BinaryRead, ParseResult};
 
-pub trait Parsable {
-    fn parse(reader: &mut BinaryReader) -> Self;
+/// Types that can be decoded from any [`BinaryRead`] source. The lifetime
+/// lets implementors borrow from an in-memory buffer instead of copying.
+pub trait Parsable<'a>: Sized {
+    fn try_parse<R: BinaryRead<'a>>(reader: &mut R) -> ParseResult<Self>;
+
+    /// Panicking counterpart of [`Parsable::try_parse`].
+    fn parse<R: BinaryRead<'a>>(reader: &mut R) -> Self {
+        Self::try_parse(reader).unwrap_or_else(|e| panic!("{}", e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::binary_reader::{BinaryReader, Parsable, ParseErrorKind};
+    use crate::binary_writer::{BinaryWriter, Writable};
+
+    #[derive(Parsable, Writable, Debug, PartialEq)]
+    struct ColorEntry {
+        #[binary(len = u16)]
+        name: String,
+        color: u32,
+    }
+
+    #[derive(Parsable, Writable, Debug, PartialEq)]
+    #[binary(magic = b"COL")]
+    struct ColorFile {
+        #[binary(pad_after = 1)]
+        version: u16,
+        entry_count: u32,
+        #[binary(count = entry_count)]
+        entries: Vec<ColorEntry>,
+        #[binary(little)]
+        data_ptr: u32,
+        #[binary(len = u8)]
+        data: Vec<u8>,
+        flags: [u8; 2],
+    }
+
+    #[derive(Parsable, Writable, Debug, PartialEq)]
+    struct Signature {
+        #[binary(magic = b"\x7fELF")]
+        signature: [u8; 4],
+        #[binary(little, count = 2)]
+        halves: Vec<u16>,
+    }
+
+    fn color_file_bytes() -> Vec<u8> {
+        vec![
+            b'C', b'O', b'L', 0x00, 0x02, 0x00, 0x00, 0x00, 0x00, 0x02, 0x00, 0x01, b'a', 0x11,
+            0x22, 0x33, 0x44, 0x00, 0x02, b'b', b'c', 0x55, 0x66, 0x77, 0x88, 0x78, 0x56, 0x34,
+            0x12, 0x03, 0x01, 0x02, 0x03, 0xaa, 0xbb,
+        ]
+    }
+
+    #[test]
+    fn test_derive_parse() {
+        let data = color_file_bytes();
+        let mut reader = BinaryReader::new(&data);
+        let file = ColorFile::try_parse(&mut reader).unwrap();
+
+        assert_eq!(file.version, 2);
+        assert_eq!(file.entry_count, 2);
+        assert_eq!(
+            file.entries[1],
+            ColorEntry {
+                name: "bc".to_string(),
+                color: 0x55667788,
+            }
+        );
+        assert_eq!(file.data_ptr, 0x12345678);
+        assert_eq!(file.data, vec![0x01, 0x02, 0x03]);
+        assert_eq!(file.flags, [0xaa, 0xbb]);
+        assert_eq!(reader.offset, data.len());
+    }
+
+    #[test]
+    fn test_derive_roundtrip() {
+        let data = color_file_bytes();
+        let file = ColorFile::parse(&mut BinaryReader::new(&data));
+
+        let mut writer = BinaryWriter::new();
+        file.write(&mut writer).unwrap();
+        assert_eq!(writer.into_inner(), data);
+    }
+
+    #[test]
+    fn test_derive_count_mismatch() {
+        let data = color_file_bytes();
+        let mut file = ColorFile::parse(&mut BinaryReader::new(&data));
+        file.entries.pop();
+
+        let error = file.write(&mut BinaryWriter::new()).unwrap_err();
+        assert_eq!(error.message, "entries has length 1 but its count is 2");
+        file.entry_count = 1;
+        file.write(&mut BinaryWriter::new()).unwrap();
+
+        let mut signature = Signature {
+            signature: *b"\x7fELF",
+            halves: vec![1],
+        };
+        assert!(signature.write(&mut BinaryWriter::new()).is_err());
+        signature.halves.push(2);
+        signature.write(&mut BinaryWriter::new()).unwrap();
+    }
+
+    #[derive(Parsable, Debug, PartialEq)]
+    struct Padded {
+        #[binary(pad_before = 1)]
+        a: u16,
+        #[binary(pad_after = 8)]
+        b: u8,
+    }
+
+    #[test]
+    fn test_derive_truncated_padding() {
+        let data = vec![0x00, 0x01, 0x02, 0x03];
+        let mut reader = BinaryReader::new(&data);
+        let error = Padded::try_parse(&mut reader).unwrap_err();
+        assert_eq!(error.kind, ParseErrorKind::UnexpectedEof { available: 0 });
+        assert_eq!(error.offset, 4);
+        assert_eq!(error.length, 8);
+
+        let error = Padded::try_parse(&mut BinaryReader::new(&[])).unwrap_err();
+        assert_eq!(error.offset, 0);
+        assert_eq!(error.length, 1);
+
+        let data = [vec![0x00, 0x01, 0x02, 0x03], vec![0; 8]].concat();
+        let mut reader = BinaryReader::new(&data);
+        let padded = Padded::try_parse(&mut reader).unwrap();
+        assert_eq!(padded, Padded { a: 0x0102, b: 0x03 });
+        assert_eq!(reader.offset, data.len());
+    }
+
+    #[test]
+    fn test_derive_bad_magic() {
+        let mut data = color_file_bytes();
+        data[0] = b'X';
+        let error = ColorFile::try_parse(&mut BinaryReader::new(&data)).unwrap_err();
+        assert!(matches!(error.kind, ParseErrorKind::InvalidData(_)));
+        assert_eq!(error.offset, 0);
+        assert_eq!(error.length, 3);
+    }
+
+    #[test]
+    fn test_derive_field_magic() {
+        let data = vec![0x7f, b'E', b'L', b'F', 0x01, 0x00, 0x02, 0x00];
+        let signature = Signature::try_parse(&mut BinaryReader::new(&data)).unwrap();
+        assert_eq!(signature.halves, vec![1, 2]);
+
+        let data = vec![0x7f, b'E', b'L', b'X', 0x01, 0x00, 0x02, 0x00];
+        let error = Signature::try_parse(&mut BinaryReader::new(&data)).unwrap_err();
+        assert_eq!(error.path_string(), "signature");
+        assert_eq!(error.offset, 0);
+        assert_eq!(error.length, 4);
+    }
+
+    #[test]
+    fn test_derive_error_path() {
+        let data = color_file_bytes();
+        let error = ColorFile::try_parse(&mut BinaryReader::new(&data[..16])).unwrap_err();
+        assert_eq!(error.path_string(), "entries > color");
+        assert_eq!(error.offset, 13);
+    }
 }
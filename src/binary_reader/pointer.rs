@@ -0,0 +1,262 @@
+use super::{BinaryRead, Parsable, ParseErrorKind, ParseResult};
+use crate::binary_writer::{BinaryWriter, Writable, WriteResult};
+use std::marker::PhantomData;
+
+/// A span of addresses backed by `size` bytes of the input at `offset`.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub struct AddressRange {
+    pub address: u64,
+    pub offset: usize,
+    pub size: u64,
+}
+
+impl AddressRange {
+    pub fn contains(&self, address: u64) -> bool {
+        address >= self.address && address - self.address < self.size
+    }
+}
+
+/// Maps the addresses stored in [`Ptr`] fields to reader offsets.
+///
+/// Without ranges an address is an offset from `base`, which covers both
+/// absolute file offsets and pointers relative to some table. With ranges,
+/// such as the loaded sections of an executable, an address must fall in one
+/// of them and is translated before `base` is added.
+#[derive(PartialEq, Debug, Clone, Default)]
+pub struct AddressSpace {
+    pub base: usize,
+    pub ranges: Vec<AddressRange>,
+
+    visiting: Vec<usize>,
+}
+
+impl AddressSpace {
+    pub fn relative(base: usize) -> AddressSpace {
+        AddressSpace {
+            base,
+            ..Default::default()
+        }
+    }
+
+    pub fn with_range(mut self, address: u64, offset: usize, size: u64) -> AddressSpace {
+        self.ranges.push(AddressRange {
+            address,
+            offset,
+            size,
+        });
+        self
+    }
+
+    pub fn translate(&self, address: u64) -> Option<usize> {
+        let offset = if self.ranges.is_empty() {
+            usize::try_from(address).ok()?
+        } else {
+            let range = self.ranges.iter().find(|range| range.contains(address))?;
+            range
+                .offset
+                .checked_add(usize::try_from(address - range.address).ok()?)?
+        };
+        offset.checked_add(self.base)
+    }
+}
+
+/// A pointer field of the width of an address in the reader's current mode,
+/// followed on demand with [`Ptr::deref`].
+pub struct Ptr<T> {
+    pub address: u64,
+    marker: PhantomData<fn() -> T>,
+}
+
+impl<T> Ptr<T> {
+    pub fn new(address: u64) -> Ptr<T> {
+        Ptr {
+            address,
+            marker: PhantomData,
+        }
+    }
+
+    pub fn is_null(&self) -> bool {
+        self.address == 0
+    }
+
+    /// Reads the target with `T::try_parse`, leaving the reader where it was.
+    pub fn deref<'a, R: BinaryRead<'a>>(
+        &self,
+        reader: &mut R,
+        space: &mut AddressSpace,
+    ) -> ParseResult<T>
+    where
+        T: Parsable<'a>,
+    {
+        self.deref_with(reader, space, |reader, _| T::try_parse(reader))
+    }
+
+    /// Reads the target with `f`, which may follow further pointers through
+    /// `space`. Fails if the address does not map into the input or leads
+    /// back to a target that is still being read.
+    pub fn deref_with<'a, R, F>(
+        &self,
+        reader: &mut R,
+        space: &mut AddressSpace,
+        f: F,
+    ) -> ParseResult<T>
+    where
+        R: BinaryRead<'a>,
+        F: FnOnce(&mut R, &mut AddressSpace) -> ParseResult<T>,
+    {
+        let target = space
+            .translate(self.address)
+            .filter(|&target| target < reader.len())
+            .ok_or_else(|| reader.error(ParseErrorKind::BadPointer(self.address), 0))?;
+        if space.visiting.contains(&target) {
+            return Err(reader.error_at(ParseErrorKind::PointerCycle(self.address), target, 0));
+        }
+
+        let offset = reader.offset();
+        space.visiting.push(target);
+        reader.seek(target);
+        let ret = reader.scoped(format!("*{:#x}", self.address), |reader| f(reader, space));
+        space.visiting.pop();
+        reader.seek(offset);
+        ret
+    }
+}
+
+impl<T> Clone for Ptr<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for Ptr<T> {}
+
+impl<T> PartialEq for Ptr<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.address == other.address
+    }
+}
+
+impl<T> std::fmt::Debug for Ptr<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "Ptr({:#x})", self.address)
+    }
+}
+
+impl<T> Default for Ptr<T> {
+    fn default() -> Self {
+        Ptr::new(0)
+    }
+}
+
+impl<'a, T> Parsable<'a> for Ptr<T> {
+    fn try_parse<R: BinaryRead<'a>>(reader: &mut R) -> ParseResult<Self> {
+        Ok(Ptr::new(reader.read_addr()?))
+    }
+}
+
+impl<T> Writable for Ptr<T> {
+    fn write(&self, writer: &mut BinaryWriter) -> WriteResult<()> {
+        writer.write_addr(self.address)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AddressSpace, Ptr};
+    use crate::binary_reader::{BinaryRead, BinaryReader, Parsable, ParseErrorKind};
+
+    #[derive(Parsable, PartialEq, Debug)]
+    struct Entry {
+        size: u32,
+        data: Ptr<u16>,
+    }
+
+    #[derive(Parsable, PartialEq, Debug)]
+    struct Header {
+        entry_count: u32,
+        table: Ptr<Entry>,
+    }
+
+    #[derive(Parsable, PartialEq, Debug)]
+    struct File {
+        magic: u32,
+        header: Ptr<Header>,
+    }
+
+    #[test]
+    fn test_deref() {
+        let data = vec![
+            0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x10, 0x00, 0x00, 0x00, 0x02, 0x11, 0x22,
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x08, 0x00, 0x00, 0x00, 0x04,
+            0x00, 0x00, 0x00, 0x0c,
+        ];
+        let mut reader = BinaryReader::new(&data);
+        let mut space = AddressSpace::default();
+
+        let file = File::parse(&mut reader);
+        assert_eq!(reader.offset, 8);
+        let header = file.header.deref(&mut reader, &mut space).unwrap();
+        assert_eq!(header.entry_count, 1);
+        assert_eq!(reader.offset, 8);
+
+        // the table pointer is relative to the header
+        let mut relative = AddressSpace::relative(0x10);
+        let entry = header.table.deref(&mut reader, &mut relative).unwrap();
+        assert_eq!(entry.size, 4);
+        assert_eq!(entry.data.deref(&mut reader, &mut space).unwrap(), 0x1122);
+    }
+
+    #[test]
+    fn test_mapped() {
+        let data = vec![0x80, 0x00, 0x10, 0x04, 0xaa, 0xbb];
+        let mut reader = BinaryReader::new(&data);
+        let mut space = AddressSpace::default().with_range(0x80001000, 0x04, 0x02);
+
+        let ptr = Ptr::<u16>::parse(&mut reader);
+        assert_eq!(space.translate(0x80001000), Some(4));
+        assert_eq!(space.translate(0x80001002), None);
+
+        let error = ptr.deref(&mut reader, &mut space).unwrap_err();
+        assert_eq!(error.kind, ParseErrorKind::BadPointer(0x80001004));
+
+        let ptr = Ptr::<u8>::new(0x80001001);
+        assert_eq!(ptr.deref(&mut reader, &mut space).unwrap(), 0xbb);
+    }
+
+    #[derive(Debug)]
+    struct Node {
+        next: Ptr<Node>,
+    }
+
+    fn parse_list<'a, R: BinaryRead<'a>>(
+        reader: &mut R,
+        space: &mut AddressSpace,
+    ) -> crate::binary_reader::ParseResult<Node> {
+        let node = Node {
+            next: Ptr::try_parse(reader)?,
+        };
+        if !node.next.is_null() {
+            node.next.deref_with(reader, space, parse_list)?;
+        }
+        Ok(node)
+    }
+
+    #[test]
+    fn test_cycle() {
+        let data = vec![
+            0x00, 0x00, 0x00, 0x04, 0x00, 0x00, 0x00, 0x08, 0x00, 0x00, 0x00, 0x04,
+        ];
+        let mut reader = BinaryReader::new(&data);
+        let mut space = AddressSpace::default();
+
+        let error = parse_list(&mut reader, &mut space).unwrap_err();
+        assert_eq!(error.kind, ParseErrorKind::PointerCycle(0x04));
+        assert_eq!(error.offset, 0x04);
+        assert_eq!(error.path_string(), "*0x4 > *0x8");
+        assert_eq!(reader.offset, 4);
+
+        let data = vec![0x00, 0x00, 0x00, 0x04, 0x00, 0x00, 0x00, 0x00];
+        let mut reader = BinaryReader::new(&data);
+        parse_list(&mut reader, &mut space).unwrap();
+    }
+}
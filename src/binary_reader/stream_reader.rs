@@ -0,0 +1,206 @@
+use super::{
+    BinaryRead, Diagnostics, Endian, Limit, ParseErrorKind, ParseLimits, ParseResult, Span,
+};
+use std::borrow::Cow;
+use std::io::{Read, Seek, SeekFrom};
+
+/// Reader over any `Read + Seek` source, such as a file or a slice of a
+/// container entry, that only loads the bytes actually requested.
+#[derive(Debug)]
+pub struct StreamReader<S> {
+    pub inner: S,
+    pub offset: usize,
+
+    pub endian: Endian,
+    pub is_64bit: bool,
+
+    pub path: Vec<String>,
+    pub diagnostics: Diagnostics,
+    pub limits: ParseLimits,
+    pub spans: Option<Vec<Span>>,
+
+    len: usize,
+    position: Option<usize>,
+}
+
+impl<S: Read + Seek> StreamReader<S> {
+    pub fn new(mut inner: S) -> std::io::Result<StreamReader<S>> {
+        let len = inner.seek(SeekFrom::End(0))? as usize;
+        Ok(StreamReader {
+            inner,
+            offset: 0,
+            endian: Endian::Big,
+            is_64bit: false,
+            path: Vec::new(),
+            diagnostics: Diagnostics::default(),
+            limits: ParseLimits::default(),
+            spans: None,
+            len,
+            position: None,
+        })
+    }
+
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+}
+
+impl<'a, S: Read + Seek> BinaryRead<'a> for StreamReader<S> {
+    fn offset(&self) -> usize {
+        self.offset
+    }
+
+    fn seek(&mut self, offset: usize) {
+        self.offset = offset;
+    }
+
+    fn endian(&self) -> Endian {
+        self.endian
+    }
+
+    fn set_endian(&mut self, endian: Endian) {
+        self.endian = endian;
+    }
+
+    fn is_64bit(&self) -> bool {
+        self.is_64bit
+    }
+
+    fn set_64bit(&mut self, is_64bit: bool) {
+        self.is_64bit = is_64bit;
+    }
+
+    fn path(&self) -> &[String] {
+        &self.path
+    }
+
+    fn path_mut(&mut self) -> &mut Vec<String> {
+        &mut self.path
+    }
+
+    fn diagnostics(&self) -> &Diagnostics {
+        &self.diagnostics
+    }
+
+    fn diagnostics_mut(&mut self) -> &mut Diagnostics {
+        &mut self.diagnostics
+    }
+
+    fn limits(&self) -> &ParseLimits {
+        &self.limits
+    }
+
+    fn spans(&self) -> Option<&[Span]> {
+        self.spans.as_deref()
+    }
+
+    fn spans_mut(&mut self) -> &mut Option<Vec<Span>> {
+        &mut self.spans
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn read_at(&mut self, pos: usize, size: usize) -> ParseResult<Cow<'a, [u8]>> {
+        self.check(pos, size)?;
+
+        if self.position != Some(pos) {
+            self.position = None;
+            self.inner
+                .seek(SeekFrom::Start(pos as u64))
+                .map_err(|e| self.error_at(ParseErrorKind::Io(e.to_string()), pos, size))?;
+        }
+
+        // unlike a slice of an in-memory buffer, the bytes are copied
+        if size > self.limits.max_allocation {
+            return Err(self.limit_error(Limit::Allocation, size, self.limits.max_allocation));
+        }
+        let mut buf = vec![0; size];
+        self.inner
+            .read_exact(&mut buf)
+            .map_err(|e| self.error_at(ParseErrorKind::Io(e.to_string()), pos, size))?;
+        self.position = Some(pos + size);
+
+        Ok(Cow::Owned(buf))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::StreamReader;
+    use crate::binary_reader::{BinaryRead, Endian, Limit, Parsable, ParseErrorKind};
+    use crate::formats::nbt::{Tag, TagPayload};
+    use std::io::Cursor;
+
+    #[test]
+    fn test_read() {
+        let data = vec![0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08];
+        let mut reader = StreamReader::new(Cursor::new(data)).unwrap();
+        assert_eq!(reader.read_u32().unwrap(), 0x01020304);
+        reader.endian = Endian::Little;
+        assert_eq!(reader.read_u16().unwrap(), 0x0605);
+        assert_eq!(reader.peek_u8().unwrap(), 0x07);
+        assert_eq!(reader.offset, 6);
+    }
+
+    #[test]
+    fn test_seek() {
+        let data = vec![0x01, 0x02, 0x03, 0x04];
+        let mut reader = StreamReader::new(Cursor::new(data)).unwrap();
+        reader.seek(2);
+        assert_eq!(reader.read_u8().unwrap(), 0x03);
+        assert_eq!(reader.read(0, 2).unwrap(), [0x01, 0x02].as_slice());
+        assert_eq!(reader.read_u8().unwrap(), 0x04);
+    }
+
+    #[test]
+    fn test_half_64bit() {
+        let data = vec![0x01, 0x02, 0x03, 0x04];
+        let mut reader = StreamReader::new(Cursor::new(data)).unwrap();
+        reader.is_64bit = true;
+        assert_eq!(reader.read_half().unwrap(), 0x01020304);
+    }
+
+    #[test]
+    fn test_eof_error() {
+        let data = vec![0x01, 0x02, 0x03];
+        let mut reader = StreamReader::new(Cursor::new(data)).unwrap();
+        reader.read_u8().unwrap();
+        let error = reader.read_u32().unwrap_err();
+        assert_eq!(error.kind, ParseErrorKind::UnexpectedEof { available: 2 });
+        assert_eq!(error.offset, 1);
+        assert_eq!(error.length, 4);
+    }
+
+    #[test]
+    fn test_allocation_limit() {
+        let data = vec![0; 16];
+        let mut reader = StreamReader::new(Cursor::new(data)).unwrap();
+        reader.limits.max_allocation = 8;
+        assert_eq!(reader.read_n_bytes(8).unwrap().len(), 8);
+        reader.seek(0);
+        let error = reader.read_n_bytes(9).err().unwrap();
+        assert_eq!(
+            error.kind,
+            ParseErrorKind::LimitExceeded {
+                limit: Limit::Allocation,
+                requested: 9,
+                max: 8,
+            }
+        );
+    }
+
+    #[test]
+    fn test_parsable() {
+        let data = vec![0x08, 0x00, 0x01, 0x61, 0x00, 0x02, 0x62, 0x63];
+        let mut reader = StreamReader::new(Cursor::new(data)).unwrap();
+        assert_eq!(
+            Tag::parse(&mut reader),
+            Tag {
+                name: "a".to_string(),
+                value: TagPayload::String("bc".to_string()),
+            }
+        );
+    }
+}
@@ -0,0 +1,307 @@
+use super::{WriteError, WriteResult};
+use crate::binary_reader::Endian;
+
+#[derive(PartialEq, Debug, Clone)]
+pub struct BinaryWriter {
+    pub data: Vec<u8>,
+    pub offset: usize,
+
+    pub endian: Endian,
+    pub is_64bit: bool,
+}
+
+impl Default for BinaryWriter {
+    fn default() -> Self {
+        Self {
+            data: Vec::new(),
+            offset: 0,
+            endian: Endian::Little,
+            is_64bit: false,
+        }
+    }
+}
+
+impl BinaryWriter {
+    pub fn new() -> BinaryWriter {
+        BinaryWriter {
+            data: Vec::new(),
+            offset: 0,
+            endian: Endian::Big,
+            is_64bit: false,
+        }
+    }
+
+    pub fn seek(&mut self, offset: usize) {
+        self.offset = offset;
+    }
+
+    pub fn into_inner(self) -> Vec<u8> {
+        self.data
+    }
+
+    pub fn error<T: ToString>(&self, message: T) -> WriteError {
+        WriteError {
+            message: message.to_string(),
+            offset: self.offset,
+        }
+    }
+
+    fn narrow<T, U>(&self, value: T, name: &str) -> WriteResult<U>
+    where
+        T: Copy + std::fmt::LowerHex,
+        U: TryFrom<T>,
+    {
+        U::try_from(value).map_err(|_| {
+            self.error(format!(
+                "{} {:#x} does not fit in {} bits",
+                name,
+                value,
+                std::mem::size_of::<U>() * 8
+            ))
+        })
+    }
+}
+
+impl BinaryWriter {
+    /// Writes `bytes` at `pos`, overwriting existing data and zero-filling
+    /// any gap past the current end. The write position is left untouched.
+    pub fn write(&mut self, pos: usize, bytes: &[u8]) {
+        let end = pos + bytes.len();
+        if self.data.len() < end {
+            self.data.resize(end, 0);
+        }
+        self.data[pos..end].copy_from_slice(bytes);
+    }
+
+    pub fn write_n_bytes(&mut self, bytes: &[u8]) {
+        self.write(self.offset, bytes);
+        self.offset += bytes.len();
+    }
+
+    pub fn write_u8(&mut self, value: u8) {
+        self.write_n_bytes(&[value]);
+    }
+
+    pub fn write_u16(&mut self, value: u16) {
+        match self.endian {
+            Endian::Big => self.write_n_bytes(&value.to_be_bytes()),
+            Endian::Little => self.write_n_bytes(&value.to_le_bytes()),
+        }
+    }
+
+    pub fn write_u32(&mut self, value: u32) {
+        match self.endian {
+            Endian::Big => self.write_n_bytes(&value.to_be_bytes()),
+            Endian::Little => self.write_n_bytes(&value.to_le_bytes()),
+        }
+    }
+
+    pub fn write_u64(&mut self, value: u64) {
+        match self.endian {
+            Endian::Big => self.write_n_bytes(&value.to_be_bytes()),
+            Endian::Little => self.write_n_bytes(&value.to_le_bytes()),
+        }
+    }
+
+    pub fn write_u128(&mut self, value: u128) {
+        match self.endian {
+            Endian::Big => self.write_n_bytes(&value.to_be_bytes()),
+            Endian::Little => self.write_n_bytes(&value.to_le_bytes()),
+        }
+    }
+}
+
+impl BinaryWriter {
+    pub fn write_i8(&mut self, value: i8) {
+        self.write_u8(value as u8);
+    }
+
+    pub fn write_i16(&mut self, value: i16) {
+        self.write_u16(value as u16);
+    }
+
+    pub fn write_i32(&mut self, value: i32) {
+        self.write_u32(value as u32);
+    }
+
+    pub fn write_i64(&mut self, value: i64) {
+        self.write_u64(value as u64);
+    }
+
+    pub fn write_f32(&mut self, value: f32) {
+        self.write_u32(value.to_bits());
+    }
+
+    pub fn write_f64(&mut self, value: f64) {
+        self.write_u64(value.to_bits());
+    }
+}
+
+impl BinaryWriter {
+    pub fn write_half(&mut self, value: u32) -> WriteResult<()> {
+        if self.is_64bit {
+            self.write_u32(value);
+        } else {
+            let value = self.narrow(value, "half")?;
+            self.write_u16(value);
+        }
+        Ok(())
+    }
+
+    pub fn write_word(&mut self, value: u64) -> WriteResult<()> {
+        if self.is_64bit {
+            self.write_u64(value);
+        } else {
+            let value = self.narrow(value, "word")?;
+            self.write_u32(value);
+        }
+        Ok(())
+    }
+
+    pub fn write_dword(&mut self, value: u128) -> WriteResult<()> {
+        if self.is_64bit {
+            self.write_u128(value);
+        } else {
+            let value = self.narrow(value, "dword")?;
+            self.write_u64(value);
+        }
+        Ok(())
+    }
+
+    pub fn write_addr(&mut self, value: u64) -> WriteResult<()> {
+        if self.is_64bit {
+            self.write_u64(value);
+        } else {
+            let value = self.narrow(value, "addr")?;
+            self.write_u32(value);
+        }
+        Ok(())
+    }
+
+    pub fn write_size(&mut self, value: usize) -> WriteResult<()> {
+        if self.is_64bit {
+            self.write_u64(value as u64);
+        } else {
+            let value = self.narrow(value, "size")?;
+            self.write_u32(value);
+        }
+        Ok(())
+    }
+}
+
+impl BinaryWriter {
+    pub fn write_u16_string(&mut self, value: &str) -> WriteResult<()> {
+        let length = self.narrow(value.len(), "string length")?;
+        self.write_u16(length);
+        self.write_n_bytes(value.as_bytes());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BinaryWriter;
+    use crate::binary_reader::{BinaryRead, BinaryReader, Endian, Parsable};
+    use crate::formats::strings::Utf8U16;
+
+    #[test]
+    fn test_big_endian_write() {
+        let mut writer = BinaryWriter::new();
+        writer.endian = Endian::Big;
+        writer.write_u64(0x0102030405060708);
+        assert_eq!(
+            writer.data,
+            vec![0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08]
+        );
+    }
+
+    #[test]
+    fn test_little_endian_write() {
+        let mut writer = BinaryWriter::new();
+        writer.endian = Endian::Little;
+        writer.write_u64(0x0102030405060708);
+        assert_eq!(
+            writer.data,
+            vec![0x08, 0x07, 0x06, 0x05, 0x04, 0x03, 0x02, 0x01]
+        );
+    }
+
+    #[test]
+    fn test_half_32bit() {
+        let mut writer = BinaryWriter::new();
+        writer.write_half(0x0102).unwrap();
+        assert_eq!(writer.data, vec![0x01, 0x02]);
+        let error = writer.write_half(0x10000).unwrap_err();
+        assert_eq!(error.message, "half 0x10000 does not fit in 16 bits");
+    }
+
+    #[test]
+    fn test_half_64bit() {
+        let mut writer = BinaryWriter::new();
+        writer.is_64bit = true;
+        writer.write_half(0x01020304).unwrap();
+        assert_eq!(writer.data, vec![0x01, 0x02, 0x03, 0x04]);
+    }
+
+    #[test]
+    fn test_word_and_addr() {
+        let mut writer = BinaryWriter::new();
+        writer.write_word(0x01020304).unwrap();
+        writer.write_addr(0x05060708).unwrap();
+        writer.write_size(0x090a0b0c).unwrap();
+        assert_eq!(
+            writer.data,
+            vec![0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b, 0x0c]
+        );
+        let error = writer.write_addr(0x1_0000_0000).unwrap_err();
+        assert_eq!(error.message, "addr 0x100000000 does not fit in 32 bits");
+        let error = writer.write_u16_string(&"a".repeat(0x10000)).unwrap_err();
+        assert_eq!(
+            error.message,
+            "string length 0x10000 does not fit in 16 bits"
+        );
+    }
+
+    #[test]
+    fn test_write_at() {
+        let mut writer = BinaryWriter::new();
+        writer.write_u32(0);
+        writer.write_u16(0xbeef);
+        writer.write(0, &[0xca, 0xfe]);
+        writer.write(8, &[0xff]);
+        assert_eq!(
+            writer.data,
+            vec![0xca, 0xfe, 0x00, 0x00, 0xbe, 0xef, 0x00, 0x00, 0xff]
+        );
+        assert_eq!(writer.offset, 6);
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let mut writer = BinaryWriter::new();
+        writer.endian = Endian::Little;
+        writer.write_u8(0x01);
+        writer.write_u16(0x0203);
+        writer.write_u32(0x04050607);
+        writer.write_u64(0x08090a0b0c0d0e0f);
+        writer.write_u128(0x101112131415161718191a1b1c1d1e1f);
+        writer.write_u16_string("abc").unwrap();
+        writer.write_i16(-2);
+        writer.write_f32(1.5);
+
+        let data = writer.into_inner();
+        let mut reader = BinaryReader::new(&data);
+        reader.endian = Endian::Little;
+        assert_eq!(reader.read_u8().unwrap(), 0x01);
+        assert_eq!(reader.read_u16().unwrap(), 0x0203);
+        assert_eq!(reader.read_u32().unwrap(), 0x04050607);
+        assert_eq!(reader.read_u64().unwrap(), 0x08090a0b0c0d0e0f);
+        assert_eq!(
+            reader.read_u128().unwrap(),
+            0x101112131415161718191a1b1c1d1e1f
+        );
+        assert_eq!(Utf8U16::try_parse(&mut reader).unwrap().0, "abc");
+        assert_eq!(reader.read_i16().unwrap(), -2);
+        assert_eq!(reader.read_f32().unwrap(), 1.5);
+    }
+}
@@ -0,0 +1,108 @@
+use super::{BinaryWriter, WriteResult};
+use crate::binary_reader::BitOrder;
+
+/// Packs values narrower than a byte into an underlying writer. A partly
+/// filled byte is padded with zero bits by [`BitWriter::align`] or when the
+/// bit writer is dropped.
+pub struct BitWriter<'w> {
+    writer: &'w mut BinaryWriter,
+    order: BitOrder,
+    current: u8,
+    used: u32,
+}
+
+impl<'w> BitWriter<'w> {
+    pub fn new(writer: &'w mut BinaryWriter, order: BitOrder) -> BitWriter<'w> {
+        BitWriter {
+            writer,
+            order,
+            current: 0,
+            used: 0,
+        }
+    }
+
+    pub fn write_bit(&mut self, bit: bool) {
+        let shift = match self.order {
+            BitOrder::MsbFirst => 7 - self.used,
+            BitOrder::LsbFirst => self.used,
+        };
+        self.current |= (bit as u8) << shift;
+        self.used += 1;
+        if self.used == 8 {
+            self.align();
+        }
+    }
+
+    /// Writes the low `count` bits of `value`, in the same order
+    /// [`BitReader::read_bits`](crate::binary_reader::BitReader::read_bits)
+    /// reads them.
+    pub fn write_bits(&mut self, value: u64, count: u32) -> WriteResult<()> {
+        if count > 64 || (count < 64 && value >> count != 0) {
+            return Err(self
+                .writer
+                .error(format!("{:#x} does not fit in {} bits", value, count)));
+        }
+        for i in 0..count {
+            let shift = match self.order {
+                BitOrder::MsbFirst => count - 1 - i,
+                BitOrder::LsbFirst => i,
+            };
+            self.write_bit(value >> shift & 1 != 0);
+        }
+        Ok(())
+    }
+
+    /// Writes out the current byte, padding it with zero bits.
+    pub fn align(&mut self) {
+        if self.used > 0 {
+            self.writer.write_u8(self.current);
+            self.current = 0;
+            self.used = 0;
+        }
+    }
+}
+
+impl Drop for BitWriter<'_> {
+    fn drop(&mut self) {
+        self.align();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BitWriter;
+    use crate::binary_reader::{BinaryReader, BitOrder, BitReader};
+    use crate::binary_writer::BinaryWriter;
+
+    #[test]
+    fn test_roundtrip() {
+        for order in [BitOrder::MsbFirst, BitOrder::LsbFirst] {
+            let mut writer = BinaryWriter::new();
+            {
+                let mut bits = BitWriter::new(&mut writer, order);
+                bits.write_bit(true);
+                bits.write_bits(0b011, 3).unwrap();
+                bits.write_bits(0x2a5, 10).unwrap();
+                assert!(bits.write_bits(4, 2).is_err());
+            }
+            let data = writer.into_inner();
+            assert_eq!(data.len(), 2);
+
+            let mut reader = BinaryReader::new(&data);
+            let mut bits = BitReader::new(&mut reader, order);
+            assert!(bits.read_bit().unwrap());
+            assert_eq!(bits.read_bits(3).unwrap(), 0b011);
+            assert_eq!(bits.read_bits(10).unwrap(), 0x2a5);
+            assert_eq!(bits.read_bits(2).unwrap(), 0);
+        }
+    }
+
+    #[test]
+    fn test_msb_first_layout() {
+        let mut writer = BinaryWriter::new();
+        let mut bits = BitWriter::new(&mut writer, BitOrder::MsbFirst);
+        bits.write_bits(0b101, 3).unwrap();
+        drop(bits);
+        assert_eq!(writer.into_inner(), vec![0b1010_0000]);
+    }
+}
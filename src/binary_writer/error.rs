@@ -0,0 +1,17 @@
+/// Error raised when a value cannot be represented in the target layout,
+/// e.g. a string too long for its `u16` length prefix.
+#[derive(PartialEq, Debug, Clone)]
+pub struct WriteError {
+    pub message: String,
+    pub offset: usize,
+}
+
+impl std::fmt::Display for WriteError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{} at {:#010x}", self.message, self.offset)
+    }
+}
+
+impl std::error::Error for WriteError {}
+
+pub type WriteResult<T> = Result<T, WriteError>;
@@ -0,0 +1,11 @@
+#[allow(clippy::module_inception)]
+mod binary_writer;
+mod bit_writer;
+mod error;
+mod writable;
+
+pub use binary_writer::BinaryWriter;
+pub use bit_writer::BitWriter;
+pub use error::{WriteError, WriteResult};
+pub use wiiu_derive::Writable;
+pub use writable::Writable;
@@ -0,0 +1,5 @@
+use super::{BinaryWriter, WriteResult};
+
+pub trait Writable {
+    fn write(&self, writer: &mut BinaryWriter) -> WriteResult<()>;
+}
@@ -1,17 +1,92 @@
 use super::entry::Entry;
-use crate::binary_reader::{BinaryReader, Parsable};
+use crate::binary_reader::{BinaryRead, Parsable, ParseResult};
+use crate::binary_writer::{BinaryWriter, Writable, WriteResult};
 
 pub struct Arc {
-    entries: Vec<Entry>,
+    pub entries: Vec<Entry>,
 }
 
-impl Parsable for Arc {
-    fn parse(reader: &mut BinaryReader) -> Self {
-        let entries_count = reader.read_u32();
-        let mut entries = Vec::with_capacity(entries_count as usize);
-        for _ in 0..entries_count {
-            entries.push(Entry::parse(reader));
+impl<'a> Parsable<'a> for Arc {
+    fn try_parse<R: BinaryRead<'a>>(reader: &mut R) -> ParseResult<Self> {
+        reader.scoped("Arc", |reader| {
+            let entries_count = reader.read_u32()? as usize;
+            let mut entries = Vec::with_capacity(reader.check_count::<Entry>(entries_count)?);
+            for i in 0..entries_count {
+                entries.push(reader.scoped(format!("Entry[{}]", i), Entry::try_parse)?);
+            }
+            Ok(Self { entries })
+        })
+    }
+}
+
+impl Writable for Arc {
+    fn write(&self, writer: &mut BinaryWriter) -> WriteResult<()> {
+        let entries_count =
+            u32::try_from(self.entries.len()).map_err(|_| writer.error("too many Arc entries"))?;
+        writer.write_u32(entries_count);
+        for entry in &self.entries {
+            entry.write(writer)?;
         }
-        Self { entries }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Arc, Entry};
+    use crate::binary_reader::{BinaryRead, BinaryReader, Parsable, ParseErrorKind};
+    use crate::binary_writer::{BinaryWriter, Writable};
+
+    #[test]
+    fn test_roundtrip() {
+        let data = vec![
+            0x00, 0x00, 0x00, 0x02, 0x00, 0x01, 0x61, 0x00, 0x00, 0x00, 0x10, 0x00, 0x00, 0x00,
+            0x04, 0x00, 0x02, 0x62, 0x63, 0x00, 0x00, 0x00, 0x24, 0x00, 0x00, 0x00, 0x08,
+        ];
+        let arc = Arc::parse(&mut BinaryReader::new(&data));
+        assert_eq!(arc.entries.len(), 2);
+        assert_eq!(arc.entries[1].path, "bc");
+        assert_eq!(arc.entries[1].ptr, 0x24);
+
+        let mut reader = BinaryReader::new(&data);
+        assert_eq!(
+            arc.entries[0].data(&mut reader).unwrap(),
+            [0x02, 0x62, 0x63, 0x00]
+        );
+        assert!(arc.entries[1].data(&mut reader).is_err());
+
+        let mut entry = arc.entries[0].reader(&mut reader).unwrap();
+        assert_eq!(entry.read_u16().unwrap(), 0x0262);
+        let error = entry.read_u32().unwrap_err();
+        assert_eq!(error.offset, 0x12);
+        assert_eq!(error.path_string(), "Entry(a)");
+
+        let mut writer = BinaryWriter::new();
+        arc.write(&mut writer).unwrap();
+        assert_eq!(writer.into_inner(), data);
+    }
+
+    #[test]
+    fn test_entry_count_limit() {
+        let data = vec![0xff, 0xff, 0xff, 0xff];
+        let mut reader = BinaryReader::new(&data);
+        let error = Arc::try_parse(&mut reader).err().unwrap();
+        assert!(matches!(error.kind, ParseErrorKind::LimitExceeded { .. }));
+        assert_eq!(error.path_string(), "Arc");
+    }
+
+    #[test]
+    fn test_write_entry() {
+        let entry = Entry {
+            path: "a".to_string(),
+            ptr: 1,
+            size: 2,
+        };
+        let mut writer = BinaryWriter::new();
+        entry.write(&mut writer).unwrap();
+        assert_eq!(
+            writer.into_inner(),
+            vec![0x00, 0x01, 0x61, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x02]
+        );
     }
 }
@@ -1,17 +1,45 @@
-use crate::{binary_reader::Parsable, formats::common::U8String};
+use crate::binary_reader::{BinaryRead, BinaryReader, Parsable, ParseResult};
+use crate::binary_writer::{BinaryWriter, Writable, WriteResult};
+use crate::formats::strings::Utf8U16;
 
 pub struct Entry {
-    path: String,
-    ptr: u32,
-    size: u32,
+    pub path: String,
+    pub ptr: u32,
+    pub size: u32,
 }
 
-impl Parsable for Entry {
-    fn parse(reader: &mut crate::binary_reader::BinaryReader) -> Self {
-        Self {
-            path: String::from_utf8_lossy(&U8String::parse(reader)).to_string(),
-            ptr: reader.read_u32(),
-            size: reader.read_u32(),
-        }
+impl<'a> Parsable<'a> for Entry {
+    fn try_parse<R: BinaryRead<'a>>(reader: &mut R) -> ParseResult<Self> {
+        Ok(Self {
+            path: Utf8U16::try_parse(reader)?.0,
+            ptr: reader.read_u32()?,
+            size: reader.read_u32()?,
+        })
+    }
+}
+
+impl Entry {
+    /// Returns the entry contents as a subslice of the archive buffer.
+    pub fn data<'a>(&self, reader: &mut BinaryReader<'a>) -> ParseResult<&'a [u8]> {
+        reader.scoped(format!("Entry({})", self.path), |reader| {
+            reader.read(self.ptr as usize, self.size as usize)
+        })
+    }
+
+    /// Returns a reader bounded to the entry contents, for parsing the
+    /// nested file without reading into its neighbours.
+    pub fn reader<'a>(&self, reader: &mut BinaryReader<'a>) -> ParseResult<BinaryReader<'a>> {
+        reader.scoped(format!("Entry({})", self.path), |reader| {
+            reader.sub_reader(self.ptr as usize, self.size as usize)
+        })
+    }
+}
+
+impl Writable for Entry {
+    fn write(&self, writer: &mut BinaryWriter) -> WriteResult<()> {
+        writer.write_u16_string(&self.path)?;
+        writer.write_u32(self.ptr);
+        writer.write_u32(self.size);
+        Ok(())
     }
 }
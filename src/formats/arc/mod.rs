@@ -1,4 +1,6 @@
+#[allow(clippy::module_inception)]
 mod arc;
 mod entry;
 
-
+pub use arc::Arc;
+pub use entry::Entry;
@@ -0,0 +1,209 @@
+use crate::binary_reader::BitOrder;
+
+/// 4-bit values packed two to a byte, as used for LCE block metadata and
+/// light levels. In [`BitOrder::LsbFirst`] order even indices are in the low
+/// nibble, like the Java edition chunk format.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct NibbleArray {
+    pub data: Vec<u8>,
+    pub order: BitOrder,
+}
+
+impl NibbleArray {
+    /// Creates an array of `len` zero nibbles, rounded up to a whole byte.
+    pub fn new(len: usize, order: BitOrder) -> NibbleArray {
+        NibbleArray {
+            data: vec![0; len.div_ceil(2)],
+            order,
+        }
+    }
+
+    pub fn from_bytes(data: &[u8], order: BitOrder) -> NibbleArray {
+        NibbleArray {
+            data: data.to_vec(),
+            order,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.data.len() * 2
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    fn shift(&self, index: usize) -> u32 {
+        match (self.order, index % 2) {
+            (BitOrder::LsbFirst, 0) | (BitOrder::MsbFirst, 1) => 0,
+            _ => 4,
+        }
+    }
+
+    pub fn get(&self, index: usize) -> Option<u8> {
+        let byte = self.data.get(index / 2)?;
+        Some(byte >> self.shift(index) & 0x0f)
+    }
+
+    /// Stores the low 4 bits of `value`. Panics if `index` is out of range.
+    pub fn set(&mut self, index: usize, value: u8) {
+        let shift = self.shift(index);
+        let byte = &mut self.data[index / 2];
+        *byte = *byte & !(0x0f << shift) | (value & 0x0f) << shift;
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = u8> + '_ {
+        (0..self.len()).map(|index| self.get(index).unwrap())
+    }
+}
+
+/// Defines a newtype over an integer with named bit flags, which parses and
+/// writes as that integer and keeps bits it has no name for.
+///
+/// ```
+/// wiiu::bit_flags! {
+///     pub struct Flags: u8 {
+///         const A = 0x01;
+///         const B = 0x02;
+///     }
+/// }
+///
+/// let flags = Flags(0x83);
+/// assert!(flags.contains(Flags::A | Flags::B));
+/// assert_eq!(flags.unknown(), 0x80);
+/// assert_eq!(format!("{:?}", flags), "Flags(A | B | 0x80)");
+/// ```
+#[macro_export]
+macro_rules! bit_flags {
+    (
+        $(#[$meta:meta])*
+        $vis:vis struct $name:ident: $ty:ty {
+            $($(#[$flag_meta:meta])* const $flag:ident = $value:expr;)*
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(PartialEq, Eq, Clone, Copy, Default, Hash)]
+        $vis struct $name(pub $ty);
+
+        impl $name {
+            $($(#[$flag_meta])* pub const $flag: $name = $name($value);)*
+
+            pub const NAMED: &'static [(&'static str, $name)] =
+                &[$((stringify!($flag), $name::$flag)),*];
+
+            pub fn bits(self) -> $ty {
+                self.0
+            }
+
+            pub fn contains(self, other: $name) -> bool {
+                self.0 & other.0 == other.0
+            }
+
+            pub fn insert(&mut self, other: $name) {
+                self.0 |= other.0;
+            }
+
+            pub fn remove(&mut self, other: $name) {
+                self.0 &= !other.0;
+            }
+
+            /// Bits that are set but have no name.
+            pub fn unknown(self) -> $ty {
+                let mut known: $ty = 0;
+                for (_, flag) in $name::NAMED {
+                    known |= flag.0;
+                }
+                self.0 & !known
+            }
+        }
+
+        impl ::std::ops::BitOr for $name {
+            type Output = $name;
+
+            fn bitor(self, other: $name) -> $name {
+                $name(self.0 | other.0)
+            }
+        }
+
+        impl ::std::ops::BitAnd for $name {
+            type Output = $name;
+
+            fn bitand(self, other: $name) -> $name {
+                $name(self.0 & other.0)
+            }
+        }
+
+        impl ::std::fmt::Debug for $name {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+                let mut names = $name::NAMED
+                    .iter()
+                    .filter(|(_, flag)| flag.0 != 0 && self.contains(*flag))
+                    .map(|(name, _)| name.to_string())
+                    .collect::<::std::vec::Vec<_>>();
+                if self.unknown() != 0 {
+                    names.push(format!("{:#x}", self.unknown()));
+                }
+                write!(f, "{}({})", stringify!($name), names.join(" | "))
+            }
+        }
+
+        impl<'a> $crate::binary_reader::Parsable<'a> for $name {
+            fn try_parse<R: $crate::binary_reader::BinaryRead<'a>>(
+                reader: &mut R,
+            ) -> $crate::binary_reader::ParseResult<Self> {
+                Ok($name(<$ty as $crate::binary_reader::Parsable<'a>>::try_parse(reader)?))
+            }
+        }
+
+        impl $crate::binary_writer::Writable for $name {
+            fn write(
+                &self,
+                writer: &mut $crate::binary_writer::BinaryWriter,
+            ) -> $crate::binary_writer::WriteResult<()> {
+                $crate::binary_writer::Writable::write(&self.0, writer)
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::NibbleArray;
+    use crate::binary_reader::{BinaryReader, BitOrder, Parsable};
+
+    #[test]
+    fn test_nibble_array() {
+        let nibbles = NibbleArray::from_bytes(&[0x21, 0x43], BitOrder::LsbFirst);
+        assert_eq!(nibbles.iter().collect::<Vec<_>>(), vec![1, 2, 3, 4]);
+        assert_eq!(nibbles.get(4), None);
+
+        let mut nibbles = NibbleArray::new(3, BitOrder::MsbFirst);
+        assert_eq!(nibbles.len(), 4);
+        nibbles.set(0, 0xa);
+        nibbles.set(1, 0x1b);
+        nibbles.set(2, 0xc);
+        assert_eq!(nibbles.data, vec![0xab, 0xc0]);
+    }
+
+    bit_flags! {
+        struct Flags: u16 {
+            const A = 0x0001;
+            const B = 0x0100;
+        }
+    }
+
+    #[test]
+    fn test_bit_flags() {
+        let data = vec![0x81, 0x01];
+        let mut reader = BinaryReader::new(&data);
+        let mut flags = Flags::parse(&mut reader);
+        assert!(flags.contains(Flags::A | Flags::B));
+        assert_eq!(flags.unknown(), 0x8000);
+
+        flags.remove(Flags::A);
+        assert_eq!(flags, Flags(0x8100));
+        assert_eq!(format!("{:?}", flags), "Flags(B | 0x8000)");
+        flags.insert(Flags::A);
+        assert_eq!(flags.bits(), 0x8101);
+    }
+}
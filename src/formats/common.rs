@@ -1,50 +1,278 @@
-use crate::binary_reader::{BinaryReader, Parsable};
+use crate::binary_reader::{BinaryRead, Parsable, ParseResult};
+use crate::binary_writer::{BinaryWriter, Writable, WriteResult};
 
 pub type U8String = Vec<u8>;
 pub type U16String = Vec<u16>;
 
-impl Parsable for U8String {
-    fn parse(reader: &mut BinaryReader) -> Self {
-        let length = reader.read_u16();
-        reader.read_n_bytes(length as usize)
+impl<'a> Parsable<'a> for U8String {
+    fn try_parse<R: BinaryRead<'a>>(reader: &mut R) -> ParseResult<Self> {
+        let length = reader.read_u16()?;
+        Ok(reader.read_n_bytes(length as usize)?.to_vec())
     }
 }
 
-impl Parsable for U16String {
-    fn parse(reader: &mut BinaryReader) -> Self {
-        let length = reader.read_u16();
-        let mut ret = vec![];
-        for _ in 0..length {
-            ret.push(reader.read_u16());
-        }
-        ret
+impl<'a> Parsable<'a> for U16String {
+    fn try_parse<R: BinaryRead<'a>>(reader: &mut R) -> ParseResult<Self> {
+        let length = reader.read_u16()?;
+        reader.read_u16_array(length as usize)
     }
 }
-impl Parsable for u8 {
-    fn parse(reader: &mut BinaryReader) -> Self {
+impl<'a> Parsable<'a> for u8 {
+    fn try_parse<R: BinaryRead<'a>>(reader: &mut R) -> ParseResult<Self> {
         reader.read_u8()
     }
 }
-impl Parsable for u16 {
-    fn parse(reader: &mut BinaryReader) -> Self {
+impl<'a> Parsable<'a> for u16 {
+    fn try_parse<R: BinaryRead<'a>>(reader: &mut R) -> ParseResult<Self> {
         reader.read_u16()
     }
 }
 
-impl Parsable for u32 {
-    fn parse(reader: &mut BinaryReader) -> Self {
+impl<'a> Parsable<'a> for u32 {
+    fn try_parse<R: BinaryRead<'a>>(reader: &mut R) -> ParseResult<Self> {
         reader.read_u32()
     }
 }
 
-impl Parsable for u64 {
-    fn parse(reader: &mut BinaryReader) -> Self {
+impl<'a> Parsable<'a> for u64 {
+    fn try_parse<R: BinaryRead<'a>>(reader: &mut R) -> ParseResult<Self> {
         reader.read_u64()
     }
 }
 
-impl Parsable for usize {
-    fn parse(reader: &mut BinaryReader) -> Self {
-        reader.read_size() as usize
+impl<'a> Parsable<'a> for usize {
+    fn try_parse<R: BinaryRead<'a>>(reader: &mut R) -> ParseResult<Self> {
+        reader.read_size()
+    }
+}
+
+macro_rules! parsable_primitive {
+    ($($ty:ty => $read:ident),* $(,)?) => {
+        $(
+            impl<'a> Parsable<'a> for $ty {
+                fn try_parse<R: BinaryRead<'a>>(reader: &mut R) -> ParseResult<Self> {
+                    reader.$read()
+                }
+            }
+        )*
+    };
+}
+
+parsable_primitive! {
+    i8 => read_i8,
+    i16 => read_i16,
+    i32 => read_i32,
+    i64 => read_i64,
+    f32 => read_f32,
+    f64 => read_f64,
+}
+
+impl<'a, T: Parsable<'a>, const N: usize> Parsable<'a> for [T; N] {
+    fn try_parse<R: BinaryRead<'a>>(reader: &mut R) -> ParseResult<Self> {
+        let mut ret = Vec::with_capacity(N);
+        for _ in 0..N {
+            ret.push(T::try_parse(reader)?);
+        }
+        Ok(ret.try_into().unwrap_or_else(|_| unreachable!()))
+    }
+}
+
+impl Writable for U8String {
+    fn write(&self, writer: &mut BinaryWriter) -> WriteResult<()> {
+        let length = u16::try_from(self.len())
+            .map_err(|_| writer.error(format!("U8String too long: {} bytes", self.len())))?;
+        writer.write_u16(length);
+        writer.write_n_bytes(self);
+        Ok(())
+    }
+}
+
+impl Writable for U16String {
+    fn write(&self, writer: &mut BinaryWriter) -> WriteResult<()> {
+        let length = u16::try_from(self.len())
+            .map_err(|_| writer.error(format!("U16String too long: {} units", self.len())))?;
+        writer.write_u16(length);
+        for unit in self {
+            writer.write_u16(*unit);
+        }
+        Ok(())
+    }
+}
+
+impl Writable for u8 {
+    fn write(&self, writer: &mut BinaryWriter) -> WriteResult<()> {
+        writer.write_u8(*self);
+        Ok(())
+    }
+}
+
+impl Writable for u16 {
+    fn write(&self, writer: &mut BinaryWriter) -> WriteResult<()> {
+        writer.write_u16(*self);
+        Ok(())
+    }
+}
+
+impl Writable for u32 {
+    fn write(&self, writer: &mut BinaryWriter) -> WriteResult<()> {
+        writer.write_u32(*self);
+        Ok(())
+    }
+}
+
+impl Writable for u64 {
+    fn write(&self, writer: &mut BinaryWriter) -> WriteResult<()> {
+        writer.write_u64(*self);
+        Ok(())
+    }
+}
+
+impl Writable for usize {
+    fn write(&self, writer: &mut BinaryWriter) -> WriteResult<()> {
+        writer.write_size(*self)
+    }
+}
+
+macro_rules! writable_primitive {
+    ($($ty:ty => $write:ident),* $(,)?) => {
+        $(
+            impl Writable for $ty {
+                fn write(&self, writer: &mut BinaryWriter) -> WriteResult<()> {
+                    writer.$write(*self);
+                    Ok(())
+                }
+            }
+        )*
+    };
+}
+
+writable_primitive! {
+    i8 => write_i8,
+    i16 => write_i16,
+    i32 => write_i32,
+    i64 => write_i64,
+    f32 => write_f32,
+    f64 => write_f64,
+}
+
+impl<T: Writable, const N: usize> Writable for [T; N] {
+    fn write(&self, writer: &mut BinaryWriter) -> WriteResult<()> {
+        for element in self {
+            element.write(writer)?;
+        }
+        Ok(())
+    }
+}
+
+/// Defines primitives decoded in a fixed byte order regardless of the
+/// reader's or writer's `endian`, for fields such as the little-endian data
+/// pointer inside the otherwise big-endian `msscmp::FileInfo`.
+macro_rules! fixed_endian {
+    ($($name:ident($ty:ty) => $from_bytes:ident, $to_bytes:ident;)*) => {
+        $(
+            #[derive(Clone, Copy, Debug, Default, PartialEq, PartialOrd)]
+            pub struct $name(pub $ty);
+
+            impl $name {
+                pub fn get(self) -> $ty {
+                    self.0
+                }
+            }
+
+            impl From<$ty> for $name {
+                fn from(value: $ty) -> Self {
+                    $name(value)
+                }
+            }
+
+            impl From<$name> for $ty {
+                fn from(value: $name) -> Self {
+                    value.0
+                }
+            }
+
+            impl<'a> Parsable<'a> for $name {
+                fn try_parse<R: BinaryRead<'a>>(reader: &mut R) -> ParseResult<Self> {
+                    Ok($name(<$ty>::$from_bytes(reader.read_array()?)))
+                }
+            }
+
+            impl Writable for $name {
+                fn write(&self, writer: &mut BinaryWriter) -> WriteResult<()> {
+                    writer.write_n_bytes(&self.0.$to_bytes());
+                    Ok(())
+                }
+            }
+        )*
+    };
+}
+
+fixed_endian! {
+    U16Be(u16) => from_be_bytes, to_be_bytes;
+    U16Le(u16) => from_le_bytes, to_le_bytes;
+    U32Be(u32) => from_be_bytes, to_be_bytes;
+    U32Le(u32) => from_le_bytes, to_le_bytes;
+    U64Be(u64) => from_be_bytes, to_be_bytes;
+    U64Le(u64) => from_le_bytes, to_le_bytes;
+    I16Be(i16) => from_be_bytes, to_be_bytes;
+    I16Le(i16) => from_le_bytes, to_le_bytes;
+    I32Be(i32) => from_be_bytes, to_be_bytes;
+    I32Le(i32) => from_le_bytes, to_le_bytes;
+    I64Be(i64) => from_be_bytes, to_be_bytes;
+    I64Le(i64) => from_le_bytes, to_le_bytes;
+    F32Be(f32) => from_be_bytes, to_be_bytes;
+    F32Le(f32) => from_le_bytes, to_le_bytes;
+    F64Be(f64) => from_be_bytes, to_be_bytes;
+    F64Le(f64) => from_le_bytes, to_le_bytes;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{F32Le, I16Be, U32Be, U32Le};
+    use crate::binary_reader::{BinaryRead, BinaryReader, Endian, Parsable};
+    use crate::binary_writer::{BinaryWriter, Writable};
+
+    #[derive(Parsable, Writable, Debug, PartialEq)]
+    struct FileInfo {
+        folder_name: u32,
+        data: U32Le,
+        play_pitch: f32,
+    }
+
+    #[test]
+    fn test_fixed_endian_ignores_reader_mode() {
+        let data = vec![0x01, 0x02, 0x03, 0x04, 0x01, 0x02, 0x03, 0x04];
+        for endian in [Endian::Big, Endian::Little] {
+            let mut reader = BinaryReader::new(&data);
+            reader.endian = endian;
+            assert_eq!(U32Be::parse(&mut reader).get(), 0x01020304);
+            assert_eq!(U32Le::parse(&mut reader).get(), 0x04030201);
+        }
+    }
+
+    #[test]
+    fn test_signed_and_float() {
+        let data = vec![0xff, 0xfe, 0x00, 0x00, 0xc0, 0x3f, 0xff];
+        let mut reader = BinaryReader::new(&data);
+        assert_eq!(I16Be::parse(&mut reader), I16Be(-2));
+        assert_eq!(F32Le::parse(&mut reader), F32Le(1.5));
+        assert_eq!(i8::parse(&mut reader), -1);
+    }
+
+    #[test]
+    fn test_mixed_endian_struct() {
+        let data = vec![
+            0x00, 0x00, 0x00, 0x10, 0x78, 0x56, 0x34, 0x12, 0x3f, 0x80, 0x00, 0x00,
+        ];
+        let mut reader = BinaryReader::new(&data);
+        let info = FileInfo::parse(&mut reader);
+        assert_eq!(info.folder_name, 0x10);
+        assert_eq!(u32::from(info.data), 0x12345678);
+        assert_eq!(info.play_pitch, 1.0);
+        assert_eq!(reader.remaining(), 0);
+
+        let mut writer = BinaryWriter::new();
+        info.write(&mut writer).unwrap();
+        assert_eq!(writer.into_inner(), data);
     }
 }
@@ -1,4 +1,6 @@
 pub mod arc;
+pub mod bits;
 pub mod common;
 pub mod nbt;
 pub mod rpx;
+pub mod strings;
@@ -1,5 +1,5 @@
 mod payload;
 mod tag;
 
-use payload::TagPayload;
+pub use payload::TagPayload;
 pub use tag::Tag;
@@ -1,9 +1,12 @@
 #![allow(overflowing_literals)]
 use super::Tag;
-use crate::binary_reader::{BinaryReader, Parsable};
+use crate::binary_reader::{BinaryRead, Parsable, ParseResult};
+use crate::binary_writer::{BinaryWriter, Writable, WriteResult};
+use crate::formats::strings::JavaUtf8;
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Default)]
 pub enum TagPayload {
+    #[default]
     End,
     Byte(i8),
     Short(i16),
@@ -18,81 +21,143 @@ pub enum TagPayload {
     IntArray(Vec<i32>),
 }
 
-impl Default for TagPayload {
-    fn default() -> Self {
-        TagPayload::End
-    }
-}
-
 impl TagPayload {
-    pub fn parse_payload(reader: &mut BinaryReader, tag_type: u8) -> Self {
-        match tag_type {
+    pub fn parse_payload<'a, R: BinaryRead<'a>>(reader: &mut R, tag_type: u8) -> ParseResult<Self> {
+        Ok(match tag_type {
             0 => TagPayload::End,
-            1 => TagPayload::Byte(reader.read_u8() as i8),
-            2 => TagPayload::Short(reader.read_u16() as i16),
-            3 => TagPayload::Int(reader.read_u32() as i32),
-            4 => TagPayload::Long(reader.read_u64() as i64),
-            5 => TagPayload::Float(f32::from_bits(reader.read_u32())),
-            6 => TagPayload::Double(f64::from_bits(reader.read_u64())),
+            1 => TagPayload::Byte(reader.read_i8()?),
+            2 => TagPayload::Short(reader.read_i16()?),
+            3 => TagPayload::Int(reader.read_i32()?),
+            4 => TagPayload::Long(reader.read_i64()?),
+            5 => TagPayload::Float(reader.read_f32()?),
+            6 => TagPayload::Double(reader.read_f64()?),
             7 => {
-                let size = reader.read_u32() as usize;
-                TagPayload::ByteArray(reader.read_n_bytes(size))
-            }
-            8 => {
-                let size = reader.read_u16();
-                let bytes = reader.read_n_bytes(size as usize);
-                let string = String::from_utf8_lossy(&bytes).to_string();
-                TagPayload::String(string)
+                let size = reader.read_u32()? as usize;
+                TagPayload::ByteArray(reader.read_n_bytes(size)?.to_vec())
             }
+            8 => TagPayload::String(JavaUtf8::try_parse(reader)?.0),
             9 => {
-                let sub_type = reader.read_u8();
-                let size = reader.read_u32() as usize;
-                let mut list = Vec::new();
-                for _ in 0..size {
-                    list.push(TagPayload::parse_payload(reader, sub_type));
+                let sub_type = reader.read_u8()?;
+                let size = reader.read_u32()? as usize;
+                // only empty lists may have the TAG_End type, which has no payload
+                if sub_type == 0 && size != 0 {
+                    return Err(reader.invalid_data(format!("List of {} TAG_End elements", size)));
+                }
+                let mut list = Vec::with_capacity(reader.check_count::<TagPayload>(size)?);
+                for i in 0..size {
+                    list.push(reader.scoped(format!("[{}]", i), |reader| {
+                        TagPayload::parse_payload(reader, sub_type)
+                    })?);
                 }
                 TagPayload::List(list)
             }
             10 => {
                 let mut elements = Vec::new();
-                while reader.peek_u8() != 0 {
-                    elements.push(Tag::parse(reader));
+                while reader.peek_u8()? != 0 {
+                    elements.push(Tag::try_parse(reader)?);
                 }
-                reader.read_u8();
+                reader.read_u8()?;
                 TagPayload::Compound(elements)
             }
             11 => {
-                let size = reader.read_u32() as usize;
-                let mut array = Vec::new();
-                for _ in 0..size {
-                    array.push(reader.read_u32() as i32);
+                let size = reader.read_u32()? as usize;
+                TagPayload::IntArray(reader.read_i32_array(size)?)
+            }
+            _ => return Err(reader.invalid_data(format!("Unknown tag type: {}", tag_type))),
+        })
+    }
+}
+
+impl TagPayload {
+    pub fn tag_type(&self) -> u8 {
+        match self {
+            TagPayload::End => 0,
+            TagPayload::Byte(_) => 1,
+            TagPayload::Short(_) => 2,
+            TagPayload::Int(_) => 3,
+            TagPayload::Long(_) => 4,
+            TagPayload::Float(_) => 5,
+            TagPayload::Double(_) => 6,
+            TagPayload::ByteArray(_) => 7,
+            TagPayload::String(_) => 8,
+            TagPayload::List(_) => 9,
+            TagPayload::Compound(_) => 10,
+            TagPayload::IntArray(_) => 11,
+        }
+    }
+
+    pub fn write_payload(&self, writer: &mut BinaryWriter) -> WriteResult<()> {
+        match self {
+            TagPayload::End => {}
+            TagPayload::Byte(value) => writer.write_i8(*value),
+            TagPayload::Short(value) => writer.write_i16(*value),
+            TagPayload::Int(value) => writer.write_i32(*value),
+            TagPayload::Long(value) => writer.write_i64(*value),
+            TagPayload::Float(value) => writer.write_f32(*value),
+            TagPayload::Double(value) => writer.write_f64(*value),
+            TagPayload::ByteArray(bytes) => {
+                writer.write_u32(TagPayload::length(writer, bytes.len())?);
+                writer.write_n_bytes(bytes);
+            }
+            TagPayload::String(string) => JavaUtf8::from(string.as_str()).write(writer)?,
+            TagPayload::List(list) => {
+                let sub_type = list.first().map_or(0, TagPayload::tag_type);
+                if list.iter().any(|element| element.tag_type() != sub_type) {
+                    return Err(writer.error("List elements have mixed tag types"));
+                }
+                if sub_type == 0 && !list.is_empty() {
+                    return Err(writer.error("List elements cannot be TAG_End"));
+                }
+                writer.write_u8(sub_type);
+                writer.write_u32(TagPayload::length(writer, list.len())?);
+                for element in list {
+                    element.write_payload(writer)?;
+                }
+            }
+            TagPayload::Compound(elements) => {
+                for element in elements {
+                    element.write(writer)?;
+                }
+                writer.write_u8(0);
+            }
+            TagPayload::IntArray(array) => {
+                writer.write_u32(TagPayload::length(writer, array.len())?);
+                for value in array {
+                    writer.write_i32(*value);
                 }
-                TagPayload::IntArray(array)
             }
-            _ => panic!("Unknown tag type: {}", tag_type),
         }
+        Ok(())
+    }
+
+    fn length(writer: &BinaryWriter, length: usize) -> WriteResult<u32> {
+        u32::try_from(length).map_err(|_| writer.error(format!("length {} too large", length)))
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::TagPayload;
-    use crate::binary_reader::BinaryReader;
+    use super::{Tag, TagPayload};
+    use crate::binary_reader::{BinaryReader, Limit, Parsable, ParseErrorKind};
+    use crate::binary_writer::{BinaryWriter, Writable};
     #[test]
     fn test_end() {
         let data = vec![];
-        let mut reader = BinaryReader::new(data);
+        let mut reader = BinaryReader::new(&data);
 
-        assert_eq!(TagPayload::parse_payload(&mut reader, 0), TagPayload::End);
+        assert_eq!(
+            TagPayload::parse_payload(&mut reader, 0).unwrap(),
+            TagPayload::End
+        );
     }
 
     #[test]
     fn test_byte() {
         let data = vec![0x12];
-        let mut reader = BinaryReader::new(data);
+        let mut reader = BinaryReader::new(&data);
 
         assert_eq!(
-            TagPayload::parse_payload(&mut reader, 1),
+            TagPayload::parse_payload(&mut reader, 1).unwrap(),
             TagPayload::Byte(0x12)
         );
     }
@@ -100,10 +165,10 @@ mod tests {
     #[test]
     fn test_short() {
         let data = vec![0x12, 0x34];
-        let mut reader = BinaryReader::new(data);
+        let mut reader = BinaryReader::new(&data);
 
         assert_eq!(
-            TagPayload::parse_payload(&mut reader, 2),
+            TagPayload::parse_payload(&mut reader, 2).unwrap(),
             TagPayload::Short(0x1234)
         );
     }
@@ -111,10 +176,10 @@ mod tests {
     #[test]
     fn test_int() {
         let data = vec![0x12, 0x34, 0x56, 0x78];
-        let mut reader = BinaryReader::new(data);
+        let mut reader = BinaryReader::new(&data);
 
         assert_eq!(
-            TagPayload::parse_payload(&mut reader, 3),
+            TagPayload::parse_payload(&mut reader, 3).unwrap(),
             TagPayload::Int(0x12345678)
         );
     }
@@ -122,10 +187,10 @@ mod tests {
     #[test]
     fn test_long() {
         let data = vec![0x12, 0x34, 0x56, 0x78, 0xaa, 0xbb, 0xcc, 0xdd];
-        let mut reader = BinaryReader::new(data);
+        let mut reader = BinaryReader::new(&data);
 
         assert_eq!(
-            TagPayload::parse_payload(&mut reader, 4),
+            TagPayload::parse_payload(&mut reader, 4).unwrap(),
             TagPayload::Long(0x12345678aabbccdd)
         );
     }
@@ -133,10 +198,10 @@ mod tests {
     #[test]
     fn test_float() {
         let data = vec![0x3f, 0x80, 0x00, 0x00];
-        let mut reader = BinaryReader::new(data);
+        let mut reader = BinaryReader::new(&data);
 
         assert_eq!(
-            TagPayload::parse_payload(&mut reader, 5),
+            TagPayload::parse_payload(&mut reader, 5).unwrap(),
             TagPayload::Float(1.0_f32)
         );
     }
@@ -144,10 +209,10 @@ mod tests {
     #[test]
     fn test_double() {
         let data = vec![0x3f, 0xf0, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00];
-        let mut reader = BinaryReader::new(data);
+        let mut reader = BinaryReader::new(&data);
 
         assert_eq!(
-            TagPayload::parse_payload(&mut reader, 6),
+            TagPayload::parse_payload(&mut reader, 6).unwrap(),
             TagPayload::Double(1.0_f64)
         );
     }
@@ -155,10 +220,10 @@ mod tests {
     #[test]
     fn test_bytearray() {
         let data = vec![0x00, 0x00, 0x00, 0x04, 0x01, 0x02, 0x03, 0x04];
-        let mut reader = BinaryReader::new(data);
+        let mut reader = BinaryReader::new(&data);
 
         assert_eq!(
-            TagPayload::parse_payload(&mut reader, 7),
+            TagPayload::parse_payload(&mut reader, 7).unwrap(),
             TagPayload::ByteArray(vec![0x01, 0x02, 0x03, 0x04])
         );
     }
@@ -166,11 +231,161 @@ mod tests {
     #[test]
     fn test_string() {
         let data = vec![0x00, 0x02, 0x61, 0x62];
-        let mut reader = BinaryReader::new(data);
+        let mut reader = BinaryReader::new(&data);
 
         assert_eq!(
-            TagPayload::parse_payload(&mut reader, 8),
+            TagPayload::parse_payload(&mut reader, 8).unwrap(),
             TagPayload::String("ab".to_string())
         );
     }
+
+    #[test]
+    fn test_unknown_type() {
+        let data = vec![];
+        let mut reader = BinaryReader::new(&data);
+
+        assert!(TagPayload::parse_payload(&mut reader, 0x42).is_err());
+    }
+
+    #[test]
+    fn test_compound() {
+        // hello_world.nbt from the NBT specification, with a nested compound
+        let mut data = vec![0x0a, 0x00, 0x0b];
+        data.extend_from_slice(b"hello world");
+        data.extend_from_slice(&[0x08, 0x00, 0x04]);
+        data.extend_from_slice(b"name");
+        data.extend_from_slice(&[0x00, 0x09]);
+        data.extend_from_slice(b"Bananrama");
+        data.extend_from_slice(&[0x0a, 0x00, 0x01, b'n', 0x01, 0x00, 0x01, b'b', 0x7f, 0x00]);
+        data.push(0x00);
+        let mut reader = BinaryReader::new(&data);
+
+        let tag = Tag::try_parse(&mut reader).unwrap();
+        assert_eq!(reader.offset, data.len());
+        assert_eq!(tag.name, "hello world");
+        assert_eq!(
+            tag.value,
+            TagPayload::Compound(vec![
+                Tag {
+                    name: "name".to_string(),
+                    value: TagPayload::String("Bananrama".to_string()),
+                },
+                Tag {
+                    name: "n".to_string(),
+                    value: TagPayload::Compound(vec![Tag {
+                        name: "b".to_string(),
+                        value: TagPayload::Byte(0x7f),
+                    }]),
+                },
+            ])
+        );
+
+        // TAG_End has no name
+        let data = vec![0x00, 0x0a];
+        let mut reader = BinaryReader::new(&data);
+        let tag = Tag::try_parse(&mut reader).unwrap();
+        assert_eq!(tag.name, "");
+        assert_eq!(tag.value, TagPayload::End);
+        assert_eq!(reader.offset, 1);
+    }
+
+    #[test]
+    fn test_truncated_tag() {
+        let data = vec![0x03, 0x00, 0x01, 0x61, 0x00, 0x00];
+        let mut reader = BinaryReader::new(&data);
+
+        let error = Tag::try_parse(&mut reader).unwrap_err();
+        assert_eq!(error.offset, 4);
+        assert_eq!(error.length, 4);
+        assert_eq!(error.path_string(), "Tag(a)");
+    }
+
+    #[test]
+    fn test_write_roundtrip() {
+        let tag = Tag {
+            name: "root".to_string(),
+            value: TagPayload::Compound(vec![
+                Tag {
+                    name: "list".to_string(),
+                    value: TagPayload::List(vec![TagPayload::Short(-2), TagPayload::Short(3)]),
+                },
+                Tag {
+                    name: "ints".to_string(),
+                    value: TagPayload::IntArray(vec![-1, 0x12345678]),
+                },
+                Tag {
+                    name: "pi".to_string(),
+                    value: TagPayload::Double(std::f64::consts::PI),
+                },
+            ]),
+        };
+
+        let mut writer = BinaryWriter::new();
+        tag.write(&mut writer).unwrap();
+        let data = writer.into_inner();
+        let mut reader = BinaryReader::new(&data);
+        assert_eq!(Tag::parse(&mut reader), tag);
+    }
+
+    #[test]
+    fn test_write_mixed_list() {
+        let payload = TagPayload::List(vec![TagPayload::Byte(1), TagPayload::Int(2)]);
+        let mut writer = BinaryWriter::new();
+        assert!(payload.write_payload(&mut writer).is_err());
+
+        let payload = TagPayload::List(vec![TagPayload::End]);
+        assert!(payload.write_payload(&mut writer).is_err());
+    }
+
+    #[test]
+    fn test_end_list() {
+        let data = vec![0x00, 0x00, 0x00, 0x00, 0x00];
+        let mut reader = BinaryReader::new(&data);
+        assert_eq!(
+            TagPayload::parse_payload(&mut reader, 9).unwrap(),
+            TagPayload::List(Vec::new())
+        );
+
+        let data = vec![0x00, 0x01, 0x00, 0x00, 0x00];
+        let mut reader = BinaryReader::new(&data);
+        let error = TagPayload::parse_payload(&mut reader, 9).unwrap_err();
+        assert!(matches!(error.kind, ParseErrorKind::InvalidData(_)));
+    }
+
+    #[test]
+    fn test_limits() {
+        let data = vec![0x01, 0xff, 0xff, 0xff, 0xff];
+        let mut reader = BinaryReader::new(&data);
+        let error = TagPayload::parse_payload(&mut reader, 9).unwrap_err();
+        assert!(matches!(
+            error.kind,
+            ParseErrorKind::LimitExceeded {
+                limit: Limit::Elements,
+                ..
+            }
+        ));
+
+        let data = [0x09, 0x00, 0x00, 0x00, 0x01].repeat(1000);
+        let mut reader = BinaryReader::new(&data);
+        let error = TagPayload::parse_payload(&mut reader, 9).unwrap_err();
+        assert!(matches!(
+            error.kind,
+            ParseErrorKind::LimitExceeded {
+                limit: Limit::Depth,
+                requested: 129,
+                max: 128,
+            }
+        ));
+
+        let data = [0x0a, 0x00, 0x00].repeat(1000);
+        let mut reader = BinaryReader::new(&data);
+        let error = Tag::try_parse(&mut reader).unwrap_err();
+        assert!(matches!(
+            error.kind,
+            ParseErrorKind::LimitExceeded {
+                limit: Limit::Depth,
+                ..
+            }
+        ));
+    }
 }
@@ -1,5 +1,7 @@
 use super::TagPayload;
-use crate::binary_reader::{BinaryReader, Parsable};
+use crate::binary_reader::{BinaryRead, Parsable, ParseResult};
+use crate::binary_writer::{BinaryWriter, Writable, WriteResult};
+use crate::formats::strings::JavaUtf8;
 
 #[derive(Debug, PartialEq)]
 pub struct Tag {
@@ -7,11 +9,27 @@ pub struct Tag {
     pub value: TagPayload,
 }
 
-impl Parsable for Tag {
-    fn parse(reader: &mut BinaryReader) -> Self {
-        let name = reader.read_u16_string();
-        let tag_type = reader.read_u8();
-        let value = TagPayload::parse_payload(reader, tag_type);
-        Tag { name, value }
+impl<'a> Parsable<'a> for Tag {
+    fn try_parse<R: BinaryRead<'a>>(reader: &mut R) -> ParseResult<Self> {
+        let tag_type = reader.read_u8()?;
+        let name = if tag_type == 0 {
+            String::new()
+        } else {
+            JavaUtf8::try_parse(reader)?.0
+        };
+        let value = reader.scoped(format!("Tag({})", name), |reader| {
+            TagPayload::parse_payload(reader, tag_type)
+        })?;
+        Ok(Tag { name, value })
+    }
+}
+
+impl Writable for Tag {
+    fn write(&self, writer: &mut BinaryWriter) -> WriteResult<()> {
+        writer.write_u8(self.value.tag_type());
+        if self.value != TagPayload::End {
+            JavaUtf8::from(self.name.as_str()).write(writer)?;
+        }
+        self.value.write_payload(writer)
     }
 }
@@ -0,0 +1,72 @@
+use flate2::Crc;
+
+/// CRC32 the loader expects for a section with contents `data`, taken over
+/// the inflated bytes. Empty sections have a CRC of 0.
+pub fn section_crc(data: &[u8]) -> u32 {
+    if data.is_empty() {
+        return 0;
+    }
+    let mut crc = Crc::new();
+    crc.update(data);
+    crc.sum()
+}
+
+/// One row of [`Rpx::verify_crcs`](super::Rpx::verify_crcs).
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub struct SectionCrc {
+    pub section: usize,
+    /// Value stored in the `SHT_RPL_CRCS` section, `None` if the table is
+    /// shorter than the section header table.
+    pub expected: Option<u32>,
+    /// `None` if the table is longer than the section header table.
+    pub actual: Option<u32>,
+}
+
+impl SectionCrc {
+    pub fn is_valid(&self) -> bool {
+        self.expected.is_some() && self.expected == self.actual
+    }
+}
+
+impl std::fmt::Display for SectionCrc {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "SectionHeader[{}]: ", self.section)?;
+        match (self.expected, self.actual) {
+            (Some(expected), Some(actual)) => write!(
+                f,
+                "expected {:#010x}, found {:#010x}{}",
+                expected,
+                actual,
+                if self.is_valid() { "" } else { " (mismatch)" }
+            ),
+            (None, Some(actual)) => write!(f, "no stored CRC, found {:#010x}", actual),
+            (Some(expected), None) => write!(f, "expected {:#010x}, no such section", expected),
+            (None, None) => write!(f, "no stored CRC and no such section"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{section_crc, SectionCrc};
+
+    #[test]
+    fn test_section_crc() {
+        assert_eq!(section_crc(b""), 0);
+        assert_eq!(section_crc(b"123456789"), 0xcbf4_3926);
+    }
+
+    #[test]
+    fn test_display() {
+        let crc = SectionCrc {
+            section: 3,
+            expected: None,
+            actual: Some(0xcbf4_3926),
+        };
+        assert!(!crc.is_valid());
+        assert_eq!(
+            crc.to_string(),
+            "SectionHeader[3]: no stored CRC, found 0xcbf43926"
+        );
+    }
+}
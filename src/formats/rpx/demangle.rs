@@ -0,0 +1,253 @@
+//! Qualified names of Green Hills (GHS) mangled C++ symbols, following
+//! `lib/ghs_demangle.py`. Only the name is recovered, so
+//! `__ct__Q2_2nn3FooFi` becomes `nn::Foo::Foo`; template arguments,
+//! parameters and return types are dropped.
+
+const SPECIAL_NAMES: &[(&str, &str)] = &[
+    ("__vtbl", "virtual table"),
+    ("__ct", "#"),
+    ("__dt", "~#"),
+    ("__as", "operator="),
+    ("__eq", "operator=="),
+    ("__ne", "operator!="),
+    ("__gt", "operator>"),
+    ("__lt", "operator<"),
+    ("__ge", "operator>="),
+    ("__le", "operator<="),
+    ("__pp", "operator++"),
+    ("__pl", "operator+"),
+    ("__apl", "operator+="),
+    ("__mi", "operator-"),
+    ("__ami", "operator-="),
+    ("__ml", "operator*"),
+    ("__amu", "operator*="),
+    ("__dv", "operator/"),
+    ("__adv", "operator/="),
+    ("__nw", "operator new"),
+    ("__dl", "operator delete"),
+    ("__vn", "operator new[]"),
+    ("__vd", "operator delete[]"),
+    ("__md", "operator%"),
+    ("__amd", "operator%="),
+    ("__mm", "operator--"),
+    ("__aa", "operator&&"),
+    ("__oo", "operator||"),
+    ("__or", "operator|"),
+    ("__aor", "operator|="),
+    ("__er", "operator^"),
+    ("__aer", "operator^="),
+    ("__ad", "operator&"),
+    ("__aad", "operator&="),
+    ("__co", "operator~"),
+    ("__cl", "operator()"),
+    ("__ls", "operator<<"),
+    ("__als", "operator<<="),
+    ("__rs", "operator>>"),
+    ("__ars", "operator>>="),
+    ("__rf", "operator->"),
+    ("__vc", "operator[]"),
+];
+
+struct Session<'s> {
+    rest: &'s str,
+}
+
+impl<'s> Session<'s> {
+    fn consume(&mut self, prefix: &str) -> bool {
+        match self.rest.strip_prefix(prefix) {
+            Some(rest) => {
+                self.rest = rest;
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.rest.bytes().next()
+    }
+
+    fn read(&mut self, len: usize) -> Option<&'s str> {
+        let ret = self.rest.get(..len)?;
+        self.rest = &self.rest[len..];
+        Some(ret)
+    }
+
+    fn read_int(&mut self) -> Option<usize> {
+        let len = self.rest.bytes().take_while(u8::is_ascii_digit).count();
+        self.read(len)?.parse().ok()
+    }
+
+    /// `<len><chars>`, without any template arguments embedded in it.
+    fn read_name(&mut self) -> Option<&'s str> {
+        let len = self.read_int()?;
+        let name = self.read(len)?;
+        let name = match name.find("tm__") {
+            Some(index) => name.get(..index.checked_sub(2)?)?,
+            None => name,
+        };
+        if self.consume("tm__") {
+            let len = self.read_int()?;
+            self.read(len)?;
+        }
+        Some(name)
+    }
+
+    /// `Q<count>_` followed by that many names, or a single name.
+    fn read_namespace(&mut self) -> Option<Vec<&'s str>> {
+        if self.consume("Q") {
+            let count = self.read_int()?;
+            if !self.consume("_") {
+                return None;
+            }
+            (0..count).map(|_| self.read_name()).collect()
+        } else {
+            Some(vec![self.read_name()?])
+        }
+    }
+}
+
+/// Longest expansion of a `__CPR` name that is accepted. Back-references can
+/// double the output each time, so a short hostile name could otherwise
+/// expand to gigabytes.
+const MAX_DECOMPRESSED_LEN: usize = 0x1000;
+
+/// Expands `__CPR<size>__` compressed names, where `J<offset>J` repeats the
+/// length-prefixed name found at `offset` of the expanded output.
+fn decompress(rest: &str) -> Option<String> {
+    let mut session = Session { rest };
+    // only a hint: like the reference implementation, a wrong size is not
+    // an error
+    let size = session.read_int()?;
+    if !session.consume("__") {
+        return None;
+    }
+    let mut ret = String::with_capacity(size.min(MAX_DECOMPRESSED_LEN));
+    for (i, token) in session.rest.split('J').enumerate() {
+        if ret.len() > MAX_DECOMPRESSED_LEN {
+            return None;
+        }
+        if i % 2 == 0 {
+            ret += token;
+        } else if token.is_empty() {
+            ret.push('J');
+        } else {
+            let offset = token.parse().ok()?;
+            let mut reference = Session {
+                rest: ret.get(offset..)?,
+            };
+            let name = reference.read_name()?.to_string();
+            ret += &format!("{}{}", name.len(), name);
+        }
+    }
+    (ret.len() <= MAX_DECOMPRESSED_LEN).then_some(ret)
+}
+
+/// Returns the qualified name of a GHS mangled symbol, or `None` if `symbol`
+/// is not mangled or uses an encoding this does not understand.
+pub fn demangle_name(symbol: &str) -> Option<String> {
+    let mut session = Session { rest: symbol };
+    if session.consume("__ghs_thunk__") {
+        session.read(12)?;
+    }
+    let decompressed;
+    if session.consume("__CPR") {
+        decompressed = decompress(session.rest)?;
+        session.rest = &decompressed;
+    }
+
+    let special = SPECIAL_NAMES
+        .iter()
+        .find(|(prefix, _)| {
+            session
+                .rest
+                .strip_prefix(prefix)
+                .is_some_and(|rest| rest.starts_with("__"))
+        })
+        .map(|(prefix, name)| {
+            session.consume(prefix);
+            *name
+        });
+
+    // the name runs up to the `__` introducing the qualifier or signature
+    let bytes = session.rest.as_bytes();
+    let end = (0..bytes.len()).find(|&i| {
+        bytes[i..].starts_with(b"__")
+            && match bytes.get(i + 2) {
+                Some(b'Q' | b'F') => true,
+                Some(c) => c.is_ascii_digit() || bytes[i + 2..].starts_with(b"tm"),
+                None => false,
+            }
+    })?;
+    let name = session.read(end)?;
+    session.consume("__");
+    if session.consume("tm__") {
+        let len = session.read_int()?;
+        session.read(len)?;
+    }
+
+    while !matches!(session.peek(), None | Some(b'Q' | b'F' | b'0'..=b'9')) {
+        session.read(1)?;
+    }
+    let namespace = match session.peek() {
+        Some(b'Q' | b'0'..=b'9') => session.read_namespace()?,
+        _ => Vec::new(),
+    };
+
+    let name = match special {
+        Some(special) => {
+            let class = namespace.last().copied().unwrap_or("auto");
+            special.replace('#', class)
+        }
+        None => name.to_string(),
+    };
+    Some(
+        namespace
+            .iter()
+            .copied()
+            .chain([name.as_str()])
+            .collect::<Vec<_>>()
+            .join("::"),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::demangle_name;
+
+    #[test]
+    fn test_demangle_name() {
+        assert_eq!(demangle_name("main"), None);
+        assert_eq!(demangle_name("tick__FUi").unwrap(), "tick");
+        assert_eq!(
+            demangle_name("tick__9MinecraftFv").unwrap(),
+            "Minecraft::tick"
+        );
+        assert_eq!(demangle_name("__ct__Q2_2nn3FooFi").unwrap(), "nn::Foo::Foo");
+        assert_eq!(demangle_name("__dt__3FooFv").unwrap(), "Foo::~Foo");
+        assert_eq!(
+            demangle_name("__aad__3FooFRC3Foo").unwrap(),
+            "Foo::operator&="
+        );
+        assert_eq!(
+            demangle_name("get__Q2_3std15vector__tm__4_iCFv").unwrap(),
+            "std::vector::get"
+        );
+        assert_eq!(
+            demangle_name("__CPR22__set__Q2_3Foo3BarFPJ8J").unwrap(),
+            "Foo::Bar::set"
+        );
+    }
+
+    #[test]
+    fn test_hostile_compressed_name() {
+        // the size is not trusted for allocation
+        assert_eq!(
+            demangle_name("__CPR99999999999999__x__3FooFv").unwrap(),
+            "Foo::x"
+        );
+        // each back-reference appends to the output, past the limit
+        let name = format!("__CPR4__3abc{}__3FooFv", "J0J".repeat(2000));
+        assert_eq!(demangle_name(&name), None);
+    }
+}
@@ -0,0 +1,357 @@
+//! Conversion of an [`Rpx`] to a plain ELF file, replacing `rpl2elf`.
+
+use super::section_header::{SectionFlags, SectionHeader, SectionKind, SectionName, SHT_PROGBITS};
+use super::Rpx;
+use crate::binary_reader::Endian;
+use crate::binary_writer::{BinaryWriter, WriteResult};
+
+pub const ET_EXEC: u16 = 2;
+pub const PT_LOAD: u32 = 1;
+
+const ELF_HEADER_SIZE: usize = 0x34;
+const PROGRAM_HEADER_SIZE: usize = 0x20;
+const SECTION_HEADER_SIZE: usize = 0x28;
+/// Largest `sh_addralign` accepted, as it decides how much padding is
+/// written before the section.
+const MAX_ALIGNMENT: u64 = 0x10000;
+
+const PF_X: u32 = 0x1;
+const PF_W: u32 = 0x2;
+const PF_R: u32 = 0x4;
+
+/// Offset of `name` in the string table `strtab`, appending it if no entry
+/// ends with it.
+fn string_offset(strtab: &mut Vec<u8>, name: &str) -> usize {
+    let mut entry = name.as_bytes().to_vec();
+    entry.push(0);
+    if let Some(offset) = strtab
+        .windows(entry.len())
+        .position(|window| window == entry)
+    {
+        return offset;
+    }
+    let offset = strtab.len();
+    strtab.extend_from_slice(&entry);
+    offset
+}
+
+/// Type and flags of `section` in the converted file.
+fn output_type(section: &SectionHeader) -> (u32, u64) {
+    let flags = section.sh_flags & !SectionFlags::RPL_ZLIB.bits();
+    match section.kind() {
+        SectionKind::RplImports => (SHT_PROGBITS, flags),
+        SectionKind::RplExports | SectionKind::RplCrcs | SectionKind::RplFileInfo => (
+            SHT_PROGBITS,
+            flags & !(SectionFlags::ALLOC | SectionFlags::EXECINSTR).bits(),
+        ),
+        _ => (section.sh_type, flags),
+    }
+}
+
+/// Where a section ends up in the converted file.
+struct Placement {
+    sh_type: u32,
+    flags: u64,
+    offset: usize,
+    file_size: usize,
+    size: u64,
+}
+
+impl Rpx<'_> {
+    /// Writes the file as an ELF32 executable that standard tools accept,
+    /// with one `PT_LOAD` segment per allocated section.
+    ///
+    /// Sections keep their index, so links, symbol section indices and
+    /// relocations stay valid. Compressed sections are written inflated, and
+    /// the RPL specific ones become `SHT_PROGBITS`: import stubs keep their
+    /// flags and addresses, while exports, CRCs and file info lose `ALLOC` and
+    /// `EXECINSTR` so they are not loaded. The OS ABI is reset to System V.
+    ///
+    /// Fails on alignments that are not a power of two or exceed 64 KiB.
+    pub fn write_elf(&self, writer: &mut BinaryWriter) -> WriteResult<()> {
+        if self.reader.is_64bit {
+            return Err(writer.error("only 32-bit files can be converted"));
+        }
+        writer.endian = self.reader.endian;
+        writer.is_64bit = false;
+        let start = writer.offset;
+
+        let str_table_index = self.elf_header.str_table_index as usize;
+        let has_strtab = str_table_index != 0 && str_table_index < self.section_headers.len();
+        let mut strtab = if has_strtab {
+            self.section_headers[str_table_index].data.to_vec()
+        } else {
+            Vec::new()
+        };
+        let names = self
+            .section_headers
+            .iter()
+            .map(|section| match &section.name {
+                SectionName::Offset(offset) => *offset,
+                SectionName::String(_) if !has_strtab => 0,
+                SectionName::String(name) => string_offset(&mut strtab, name),
+            })
+            .collect::<Vec<_>>();
+
+        let is_loaded = |section: &SectionHeader| {
+            section.kind() != SectionKind::Null
+                && output_type(section).1 & SectionFlags::ALLOC.bits() != 0
+        };
+        let segment_count = self
+            .section_headers
+            .iter()
+            .filter(|section| is_loaded(section))
+            .count();
+
+        // contents follow the ELF and program headers in section order
+        let mut offset = ELF_HEADER_SIZE + segment_count * PROGRAM_HEADER_SIZE;
+        let mut placements = Vec::with_capacity(self.section_headers.len());
+        for (i, section) in self.section_headers.iter().enumerate() {
+            let (sh_type, flags) = output_type(section);
+            if section.kind() == SectionKind::Null {
+                placements.push(Placement {
+                    sh_type,
+                    flags,
+                    offset: 0,
+                    file_size: 0,
+                    size: 0,
+                });
+                continue;
+            }
+            // 0 and 1 both mean no alignment
+            let alignment = section.alignment;
+            if alignment > MAX_ALIGNMENT || (alignment > 1 && !alignment.is_power_of_two()) {
+                return Err(writer.error(format!(
+                    "SectionHeader[{}]: unsupported alignment {:#x}",
+                    i, alignment
+                )));
+            }
+            let alignment = alignment.max(1) as usize;
+            let data: &[u8] = if i == str_table_index && has_strtab {
+                &strtab
+            } else {
+                &section.data
+            };
+            let (size, file_size) = match section.kind() {
+                SectionKind::NoBits => (section.size, 0),
+                _ => (data.len() as u64, data.len()),
+            };
+            offset = offset.next_multiple_of(alignment);
+            // loaders need segment offsets congruent to their addresses
+            if is_loaded(section) {
+                offset += section.address as usize % alignment;
+            }
+            writer.write(start + offset, data);
+            placements.push(Placement {
+                sh_type,
+                flags,
+                offset,
+                file_size,
+                size,
+            });
+            offset += file_size;
+        }
+        let section_header_offset = offset.next_multiple_of(4);
+
+        writer.seek(start);
+        writer.write_n_bytes(b"\x7fELF");
+        writer.write_u8(1);
+        writer.write_u8(match self.reader.endian {
+            Endian::Little => 1,
+            Endian::Big => 2,
+        });
+        writer.write_u8(1);
+        writer.write_n_bytes(&[0; 9]);
+        writer.write_u16(ET_EXEC);
+        writer.write_u16(self.elf_header.e_machine);
+        writer.write_u32(1);
+        writer.write_addr(self.elf_header.e_entry)?;
+        writer.write_size(if segment_count == 0 {
+            0
+        } else {
+            ELF_HEADER_SIZE
+        })?;
+        writer.write_size(section_header_offset)?;
+        writer.write_u32(self.elf_header.e_flags);
+        writer.write_u16(ELF_HEADER_SIZE as u16);
+        writer.write_u16(PROGRAM_HEADER_SIZE as u16);
+        writer.write_u16(segment_count as u16);
+        writer.write_u16(SECTION_HEADER_SIZE as u16);
+        writer.write_u16(self.section_headers.len() as u16);
+        writer.write_u16(if has_strtab {
+            str_table_index as u16
+        } else {
+            0
+        });
+
+        for (section, placement) in self.section_headers.iter().zip(&placements) {
+            if !is_loaded(section) {
+                continue;
+            }
+            let mut flags = PF_R;
+            if placement.flags & SectionFlags::WRITE.bits() != 0 {
+                flags |= PF_W;
+            }
+            if placement.flags & SectionFlags::EXECINSTR.bits() != 0 {
+                flags |= PF_X;
+            }
+            writer.write_u32(PT_LOAD);
+            writer.write_size(placement.offset)?;
+            writer.write_addr(section.address)?;
+            writer.write_addr(section.address)?;
+            writer.write_size(placement.file_size)?;
+            writer.write_word(placement.size)?;
+            writer.write_u32(flags);
+            writer.write_word(section.alignment.max(1))?;
+        }
+
+        writer.seek(start + section_header_offset);
+        for ((section, name), placement) in self.section_headers.iter().zip(names).zip(placements) {
+            if section.kind() == SectionKind::Null {
+                writer.write_n_bytes(&[0; SECTION_HEADER_SIZE]);
+                continue;
+            }
+            writer.write_size(name)?;
+            writer.write_u32(placement.sh_type);
+            writer.write_word(placement.flags)?;
+            writer.write_addr(section.address)?;
+            writer.write_size(placement.offset)?;
+            writer.write_word(placement.size)?;
+            writer.write_u32(section.sh_link as u32);
+            writer.write_u32(section.sh_info as u32);
+            writer.write_word(section.alignment)?;
+            writer.write_word(section.sh_ent_size)?;
+        }
+        Ok(())
+    }
+
+    /// See [`Rpx::write_elf`].
+    pub fn to_elf(&self) -> WriteResult<Vec<u8>> {
+        let mut writer = BinaryWriter::new();
+        self.write_elf(&mut writer)?;
+        Ok(writer.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ET_EXEC, PT_LOAD};
+    use crate::binary_reader::BinaryReader;
+    use crate::formats::rpx::section_header::{
+        SectionFlags, SectionKind, SHT_NOBITS, SHT_RPL_EXPORTS, SHT_RPL_FILEINFO, SHT_RPL_IMPORTS,
+        SHT_STRTAB, SHT_SYMTAB,
+    };
+    use crate::formats::rpx::test_elf::{self, TestSection};
+    use crate::formats::rpx::Rpx;
+    use flate2::write::ZlibEncoder;
+    use flate2::Compression;
+    use std::io::Write;
+
+    #[test]
+    fn test_to_elf() {
+        let code = (0..64).collect::<Vec<u8>>();
+        let mut compressed = (code.len() as u32).to_be_bytes().to_vec();
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(&code).unwrap();
+        compressed.extend(encoder.finish().unwrap());
+        let mut text = TestSection::new(".text", 1, compressed);
+        text.flags = 0x0800_0006;
+        text.address = 0x0200_0000;
+
+        let mut bss = TestSection::new(".bss", SHT_NOBITS, Vec::new());
+        bss.flags = 0x3;
+        bss.address = 0x1000_0000;
+        bss.size = Some(0x100);
+        let mut symbols = vec![0; 16];
+        for value in [1u32, 0x0200_0010, 0x20, 0x1201_0001] {
+            symbols.extend_from_slice(&value.to_be_bytes());
+        }
+        let mut symtab = TestSection::new(".symtab", SHT_SYMTAB, symbols);
+        symtab.link = 4;
+        let strtab = TestSection::new(".strtab", SHT_STRTAB, b"\0main\0".to_vec());
+        let mut fimport = TestSection::new(
+            ".fimport_coreinit",
+            SHT_RPL_IMPORTS,
+            b"\0\0\0\x01\0\0\0\0coreinit\0\0\0\0".to_vec(),
+        );
+        fimport.flags = 0x6;
+        fimport.address = 0x0210_0000;
+        let file_info = TestSection::new(".rplfileinfo", SHT_RPL_FILEINFO, vec![0; 0x60]);
+        let mut exports = [1u32, 0, 0x0200_0010, 0x10]
+            .iter()
+            .flat_map(|value| value.to_be_bytes())
+            .collect::<Vec<_>>();
+        exports.extend_from_slice(b"main\0");
+        let mut fexports = TestSection::new(".fexports", SHT_RPL_EXPORTS, exports);
+        fexports.flags = 0x6;
+        fexports.address = 0x1000_8000;
+        let data = test_elf::build(vec![
+            text, bss, symtab, strtab, fimport, file_info, fexports,
+        ]);
+        let rpx = Rpx::try_parse(BinaryReader::new(&data)).unwrap();
+        assert_eq!(rpx.exports.len(), 1);
+
+        let elf = rpx.to_elf().unwrap();
+        let converted = Rpx::try_parse(BinaryReader::new(&elf)).unwrap();
+        assert_eq!(converted.elf_header.e_type, ET_EXEC);
+        assert_eq!(converted.elf_header.e_ident.os_abi, 0);
+        assert!(converted.reader.diagnostics.warnings().next().is_none());
+        assert_eq!(converted.section_headers.len(), rpx.section_headers.len());
+        for (before, after) in rpx.section_headers.iter().zip(&converted.section_headers) {
+            assert_eq!(before.name, after.name);
+            assert_eq!(before.address, after.address);
+            assert_eq!(before.data, after.data);
+        }
+
+        let text = &converted.section_headers[1];
+        assert_eq!(text.data, code);
+        assert_eq!(text.size, 64);
+        assert!(!text.flags().contains(SectionFlags::RPL_ZLIB));
+        assert_eq!(text.offset % 4, 0);
+        assert_eq!(converted.section_headers[2].size, 0x100);
+        assert_eq!(converted.section_headers[5].kind(), SectionKind::ProgBits);
+        assert_eq!(converted.section_headers[6].kind(), SectionKind::ProgBits);
+        let fimport = &converted.section_headers[5];
+        assert!(fimport
+            .flags()
+            .contains(SectionFlags::ALLOC | SectionFlags::EXECINSTR));
+        let fexports = &converted.section_headers[7];
+        assert_eq!(fexports.kind(), SectionKind::ProgBits);
+        assert!(!fexports.flags().contains(SectionFlags::ALLOC));
+        assert!(!fexports.flags().contains(SectionFlags::EXECINSTR));
+        assert!(converted.section_for_vaddr(0x1000_8000).is_none());
+        assert!(converted.exports.is_empty());
+        assert_eq!(converted.symbol_by_name("main").unwrap().shndx, 1);
+        assert!(converted.imports.is_empty());
+
+        // .text, .bss and the import stubs are loaded
+        assert_eq!(converted.program_headers.len(), 3);
+        let segment = &converted.program_headers[0];
+        assert_eq!(segment.ph_type, PT_LOAD as u64);
+        assert_eq!(segment.offset, text.offset);
+        assert_eq!(segment.virtual_address, 0x0200_0000);
+        assert_eq!(segment.file_size, 64);
+        assert_eq!(segment.ph_flags, 0x5);
+        let segment = &converted.program_headers[1];
+        assert_eq!(segment.file_size, 0);
+        assert_eq!(segment.mem_size, 0x100);
+        assert_eq!(segment.ph_flags, 0x6);
+        assert_eq!(converted.program_headers[2].virtual_address, 0x0210_0000);
+    }
+
+    #[test]
+    fn test_bad_alignment() {
+        let data = test_elf::build(vec![TestSection::new(".text", 1, vec![0; 4])]);
+        let mut rpx = Rpx::try_parse(BinaryReader::new(&data)).unwrap();
+        rpx.section_headers[1].alignment = 0x8000_0000;
+        let error = rpx.to_elf().unwrap_err();
+        assert_eq!(
+            error.message,
+            "SectionHeader[1]: unsupported alignment 0x80000000"
+        );
+        rpx.section_headers[1].alignment = 3;
+        assert!(rpx.to_elf().is_err());
+        rpx.section_headers[1].alignment = 0;
+        rpx.to_elf().unwrap();
+    }
+}
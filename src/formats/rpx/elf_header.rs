@@ -1,7 +1,7 @@
 use super::elf_identifier::ELFIdentifier;
-use crate::binary_reader::BinaryReader;
+use crate::binary_reader::{BinaryRead, Parsable, ParseResult};
 
-#[derive(Debug)]
+#[derive(Debug, Default)]
 pub struct ELFHeader {
     pub e_ident: ELFIdentifier,
     pub e_type: u16,
@@ -19,51 +19,30 @@ pub struct ELFHeader {
     pub str_table_index: u16,
 }
 
-impl ELFHeader {
-    pub fn default() -> ELFHeader {
-        ELFHeader {
-            e_ident: ELFIdentifier::default(),
-            e_type: 0,
-            e_machine: 0,
-            e_version: 0,
-            e_entry: 0,
-            program_header_offset: 0,
-            section_header_offset: 0,
-            e_flags: 0,
-            elf_header_size: 0,
-            program_header_size: 0,
-            program_headers_count: 0,
-            section_header_size: 0,
-            section_header_count: 0,
-            str_table_index: 0,
-        }
-    }
-
-    pub fn parse(reader: &mut BinaryReader) -> ELFHeader {
-        let mut ret = ELFHeader::default();
-
-        ret.e_ident = ELFIdentifier::parse(reader);
-
-        ret.e_type = reader.read_u16();
-        ret.e_machine = reader.read_u16();
-        ret.e_version = reader.read_u32();
-        ret.e_entry = reader.read_addr();
-        ret.program_header_offset = reader.read_addr();
-        ret.section_header_offset = reader.read_addr();
-        ret.e_flags = reader.read_u32();
-        ret.elf_header_size = reader.read_u16();
-        ret.program_header_size = reader.read_u16();
-        ret.program_headers_count = reader.read_u16();
-        ret.section_header_size = reader.read_u16();
-        ret.section_header_count = reader.read_u16();
-        ret.str_table_index = reader.read_u16();
-        ret
+impl<'a> Parsable<'a> for ELFHeader {
+    fn try_parse<R: BinaryRead<'a>>(reader: &mut R) -> ParseResult<ELFHeader> {
+        Ok(ELFHeader {
+            e_ident: reader.scoped("ELFIdentifier", ELFIdentifier::try_parse)?,
+            e_type: reader.read_u16()?,
+            e_machine: reader.read_u16()?,
+            e_version: reader.read_u32()?,
+            e_entry: reader.read_addr()?,
+            program_header_offset: reader.read_addr()?,
+            section_header_offset: reader.read_addr()?,
+            e_flags: reader.read_u32()?,
+            elf_header_size: reader.read_u16()?,
+            program_header_size: reader.read_u16()?,
+            program_headers_count: reader.read_u16()?,
+            section_header_size: reader.read_u16()?,
+            section_header_count: reader.read_u16()?,
+            str_table_index: reader.read_u16()?,
+        })
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::binary_reader::BinaryReader;
+    use crate::binary_reader::{BinaryReader, Parsable};
 
     use super::ELFHeader;
 
@@ -76,7 +55,7 @@ mod tests {
             0x00, 0x00, 0x00, 0x00, 0x00, 0x28, 0x00, 0x12, 0x00, 0x0f, 0x00, 0x00, 0x00, 0x00,
             0x00, 0x00, 0x00, 0x00,
         ];
-        let mut reader = BinaryReader::new(data);
+        let mut reader = BinaryReader::new(&data);
         let elf_header = ELFHeader::parse(&mut reader);
 
         assert_eq!(elf_header.e_type, 0xfe01);
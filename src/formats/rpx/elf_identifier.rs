@@ -1,9 +1,9 @@
-use crate::binary_reader::{BinaryReader, Endian};
+use crate::binary_reader::{BinaryRead, DiagnosticCode, Endian, Parsable, ParseResult, Severity};
 
 #[derive(Debug)]
 pub struct ELFIdentifier {
-    os_abi: u8,
-    abi_version: u8,
+    pub os_abi: u8,
+    pub abi_version: u8,
 }
 
 impl ELFIdentifier {
@@ -13,73 +13,99 @@ impl ELFIdentifier {
             abi_version,
         }
     }
+}
 
-    pub fn default() -> ELFIdentifier {
+impl Default for ELFIdentifier {
+    fn default() -> ELFIdentifier {
         ELFIdentifier::new(0, 0)
     }
+}
 
-    pub fn parse(reader: &mut BinaryReader) -> ELFIdentifier {
-        let signature = reader.read_n_bytes(4);
-
-        if signature != b"\x7fELF" {
-            eprintln!("Warning: Invalid ELF signature");
+impl<'a> Parsable<'a> for ELFIdentifier {
+    fn try_parse<R: BinaryRead<'a>>(reader: &mut R) -> ParseResult<ELFIdentifier> {
+        let start = reader.offset();
+        let signature = reader.read_n_bytes(4)?;
+
+        if *signature != *b"\x7fELF" {
+            reader.warn_at(
+                DiagnosticCode::BadElfMagic,
+                start,
+                4,
+                format!("invalid ELF signature {:02x?}", signature),
+            )?;
         }
 
-        let class = reader.read_u8();
+        let class = reader.read_u8()?;
 
-        let data = reader.read_u8();
+        let data = reader.read_u8()?;
 
-        if reader.read_u8() != 1 {
-            eprintln!("Warning: Invalid ELF Version");
+        let version = reader.read_u8()?;
+        if version != 1 {
+            reader.warn_at(
+                DiagnosticCode::BadElfVersion,
+                start + 6,
+                1,
+                format!("invalid ELF version {}", version),
+            )?;
         }
 
-        let os_abi = reader.read_u8();
+        let os_abi = reader.read_u8()?;
 
-        let abi_version = reader.read_u8();
+        let abi_version = reader.read_u8()?;
 
-        reader.offset += 16 - 9;
+        // padding
+        reader.read_n_bytes(16 - 9)?;
 
         let ret = ELFIdentifier::new(os_abi, abi_version);
 
         match class {
             1 => {
-                reader.is_64bit = false;
+                reader.set_64bit(false);
             }
             2 => {
-                reader.is_64bit = true;
-            }
-            _ => {
-                eprintln!("Warning: Invalid ELF Class")
+                reader.set_64bit(true);
             }
+            _ => reader.warn_at(
+                DiagnosticCode::UnknownElfClass,
+                start + 4,
+                1,
+                format!("invalid ELF class {}", class),
+            )?,
         }
 
         match data {
             1 => {
-                reader.endian = Endian::Little;
+                reader.set_endian(Endian::Little);
             }
             2 => {
-                reader.endian = Endian::Big;
-            }
-            _ => {
-                eprintln!("Warning: Invalid ELF Data Encoding")
+                reader.set_endian(Endian::Big);
             }
+            _ => reader.warn_at(
+                DiagnosticCode::UnknownElfEncoding,
+                start + 5,
+                1,
+                format!("invalid ELF data encoding {}", data),
+            )?,
         }
 
-        println!(
-            "Use {}-{} mode",
-            (if reader.is_64bit { "64bit" } else { "32bit" }),
-            reader.endian
+        let layout = format!(
+            "using {}-{} mode",
+            (if reader.is_64bit() { "64bit" } else { "32bit" }),
+            reader.endian()
         );
-        ret
+        reader.report_at(Severity::Info, DiagnosticCode::ElfLayout, start, 16, layout)?;
+        Ok(ret)
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::binary_reader::{BinaryReader, Endian};
+    use crate::binary_reader::{
+        BinaryReader, DiagnosticCode, DiagnosticMode, Endian, Parsable, ParseErrorKind,
+    };
 
     fn auto_test(is_64bit: bool, is_big_endian: bool) {
-        let mut reader = BinaryReader::new(vec![
+        let data = vec![
             0x7f,
             0x45,
             0x4c,
@@ -96,7 +122,8 @@ mod tests {
             0x00,
             0x00,
             0x00,
-        ]);
+        ];
+        let mut reader = BinaryReader::new(&data);
 
         let id = super::ELFIdentifier::parse(&mut reader);
 
@@ -131,4 +158,35 @@ mod tests {
     fn test_64bit_big() {
         auto_test(true, true);
     }
+
+    #[test]
+    fn test_diagnostics() {
+        let data = vec![
+            0x7f, 0x45, 0x4c, 0x47, 0x03, 0x02, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00,
+        ];
+        let mut reader = BinaryReader::new(&data);
+        super::ELFIdentifier::parse(&mut reader);
+
+        let warnings = reader.diagnostics.warnings().collect::<Vec<_>>();
+        assert_eq!(warnings.len(), 2);
+        assert_eq!(warnings[0].code, DiagnosticCode::BadElfMagic);
+        assert_eq!(warnings[0].offset, 0);
+        assert_eq!(warnings[1].code, DiagnosticCode::UnknownElfClass);
+        assert_eq!(warnings[1].offset, 4);
+        assert!(reader.diagnostics.contains(DiagnosticCode::ElfLayout));
+
+        let mut reader = BinaryReader::new(&data);
+        reader.diagnostics.mode = DiagnosticMode::Strict;
+        let error = super::ELFIdentifier::try_parse(&mut reader).unwrap_err();
+        assert!(matches!(
+            error.kind,
+            ParseErrorKind::Diagnostic(DiagnosticCode::BadElfMagic, _)
+        ));
+
+        let mut reader = BinaryReader::new(&data);
+        reader.diagnostics.mode = DiagnosticMode::Ignore;
+        super::ELFIdentifier::parse(&mut reader);
+        assert!(reader.diagnostics.entries.is_empty());
+    }
 }
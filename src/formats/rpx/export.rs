@@ -0,0 +1,143 @@
+use super::import::RplKind;
+use crate::binary_reader::{BinaryRead, Parsable, ParseResult};
+use crate::formats::strings::NulTerminated;
+use std::collections::HashMap;
+
+/// Set in the name offset of an export of a thread-local variable.
+pub const EXPORT_TLS: u32 = 0x8000_0000;
+
+#[derive(PartialEq, Debug, Clone)]
+pub struct RplExport {
+    /// Address of the exported function or variable.
+    pub value: u32,
+    /// Offset of the name from the start of the section, with
+    /// [`EXPORT_TLS`] possibly set.
+    pub name_offset: u32,
+    pub name: String,
+}
+
+impl RplExport {
+    pub fn is_tls(&self) -> bool {
+        self.name_offset & EXPORT_TLS != 0
+    }
+}
+
+/// A `SHT_RPL_EXPORTS` section: a count and signature, `count` pairs of
+/// value and name offset, then the names they point to.
+#[derive(PartialEq, Debug, Clone)]
+pub struct RplExports {
+    pub signature: u32,
+    pub entries: Vec<RplExport>,
+
+    /// Index of the export section.
+    pub section: usize,
+    pub kind: RplKind,
+}
+
+impl<'a> Parsable<'a> for RplExports {
+    fn try_parse<R: BinaryRead<'a>>(reader: &mut R) -> ParseResult<RplExports> {
+        let start = reader.offset();
+        let count = reader.read_u32()? as usize;
+        let signature = reader.read_u32()?;
+        let count = reader.check_count::<RplExport>(count)?;
+        let mut entries = Vec::with_capacity(count.min(reader.remaining() / 8));
+        for i in 0..count {
+            let entry = reader.scoped(format!("RplExport[{}]", i), |reader| {
+                let value = reader.read_u32()?;
+                let name_offset = reader.read_u32()?;
+                let end = reader.offset();
+                reader.seek(start + (name_offset & !EXPORT_TLS) as usize);
+                let name = reader.scoped("name", NulTerminated::try_parse);
+                reader.seek(end);
+                Ok(RplExport {
+                    value,
+                    name_offset,
+                    name: name?.0,
+                })
+            })?;
+            entries.push(entry);
+        }
+        Ok(RplExports {
+            signature,
+            entries,
+            section: 0,
+            kind: RplKind::Function,
+        })
+    }
+}
+
+/// Lookup tables over export sections, from names and values to the first
+/// matching export as `(section, entry)` indices into the list.
+#[derive(Debug, Default)]
+pub struct ExportIndex {
+    by_name: HashMap<String, (usize, usize)>,
+    by_value: HashMap<u32, (usize, usize)>,
+}
+
+impl ExportIndex {
+    pub fn new(exports: &[RplExports]) -> ExportIndex {
+        let mut ret = ExportIndex::default();
+        for (i, exports) in exports.iter().enumerate() {
+            for (j, export) in exports.entries.iter().enumerate() {
+                ret.by_name.entry(export.name.clone()).or_insert((i, j));
+                ret.by_value.entry(export.value).or_insert((i, j));
+            }
+        }
+        ret
+    }
+
+    pub fn by_name(&self, name: &str) -> Option<(usize, usize)> {
+        self.by_name.get(name).copied()
+    }
+
+    pub fn at_address(&self, address: u64) -> Option<(usize, usize)> {
+        self.by_value.get(&u32::try_from(address).ok()?).copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ExportIndex, RplExports};
+    use crate::binary_reader::{BinaryRead, BinaryReader, Parsable};
+
+    #[test]
+    fn test_parse() {
+        let mut data = vec![
+            0x00, 0x00, 0x00, 0x02, 0xca, 0xfe, 0xba, 0xbe, 0x02, 0x00, 0x00, 0x10, 0x00, 0x00,
+            0x00, 0x18, 0x10, 0x00, 0x00, 0x20, 0x80, 0x00, 0x00, 0x21,
+        ];
+        data.extend_from_slice(b"OSReport\0tls\0");
+
+        let exports = RplExports::parse(&mut BinaryReader::new(&data));
+        assert_eq!(exports.signature, 0xcafe_babe);
+        assert_eq!(exports.entries.len(), 2);
+        assert_eq!(exports.entries[0].value, 0x0200_0010);
+        assert_eq!(exports.entries[0].name, "OSReport");
+        assert!(!exports.entries[0].is_tls());
+        assert_eq!(exports.entries[1].name, "tls");
+        assert!(exports.entries[1].is_tls());
+
+        // name offsets are relative to the start of the section
+        let mut shifted = vec![0xff; 4];
+        shifted.extend_from_slice(&data);
+        let mut reader = BinaryReader::new(&shifted);
+        reader.seek(4);
+        assert_eq!(RplExports::parse(&mut reader), exports);
+    }
+
+    #[test]
+    fn test_index() {
+        let mut data = vec![
+            0x00, 0x00, 0x00, 0x02, 0x00, 0x00, 0x00, 0x00, 0x02, 0x00, 0x00, 0x10, 0x00, 0x00,
+            0x00, 0x18, 0x02, 0x00, 0x00, 0x10, 0x00, 0x00, 0x00, 0x1a,
+        ];
+        data.extend_from_slice(b"a\0b\0");
+        let exports = [RplExports::parse(&mut BinaryReader::new(&data))];
+        let index = ExportIndex::new(&exports);
+        assert_eq!(index.by_name("b"), Some((0, 1)));
+        assert_eq!(index.by_name("c"), None);
+        // the first of two exports at the same address
+        assert_eq!(index.at_address(0x0200_0010), Some((0, 0)));
+        assert_eq!(index.at_address(0x1_0200_0010), None);
+    }
+}
@@ -0,0 +1,189 @@
+use crate::binary_reader::{BinaryRead, Parsable, ParseErrorKind, ParseResult};
+use crate::formats::strings::NulTerminated;
+
+crate::bit_flags! {
+    /// `RplFileInfo::flags` bits.
+    pub struct RplFileFlags: u32 {
+        /// The module is the main executable rather than a library.
+        const IS_RPX = 0x2;
+    }
+}
+
+const VERSION: u32 = 0xcafe_0402;
+
+/// Contents of the `SHT_RPL_FILEINFO` section, in the version 4.2 layout
+/// (`version` 0xcafe0402), the only one that is accepted. `filename` and `tags` are read from the string
+/// area of the section that `filename_offset` and `tag_offset` point into.
+#[derive(PartialEq, Debug, Clone, Default)]
+pub struct RplFileInfo {
+    pub version: u32,
+    pub text_size: u32,
+    pub text_align: u32,
+    pub data_size: u32,
+    pub data_align: u32,
+    pub load_size: u32,
+    pub load_align: u32,
+    pub temp_size: u32,
+    pub tramp_adjust: u32,
+    pub sda_base: u32,
+    pub sda2_base: u32,
+    pub stack_size: u32,
+    pub filename_offset: u32,
+    pub flags: RplFileFlags,
+    pub heap_size: u32,
+    pub tag_offset: u32,
+    pub min_version: u32,
+    pub compression_level: i32,
+    pub tramp_addition: u32,
+    pub file_info_pad: u32,
+    pub cafe_sdk_version: u32,
+    pub cafe_sdk_revision: u32,
+    pub tls_module_index: u16,
+    pub tls_align_shift: u16,
+    pub runtime_file_info_size: u32,
+
+    pub filename: Option<String>,
+    /// Key and value pairs, in order.
+    pub tags: Vec<(String, String)>,
+}
+
+impl RplFileInfo {
+    pub fn is_rpx(&self) -> bool {
+        self.flags.contains(RplFileFlags::IS_RPX)
+    }
+
+    /// The value of the first tag named `key`.
+    pub fn tag(&self, key: &str) -> Option<&str> {
+        self.tags
+            .iter()
+            .find(|(name, _)| name == key)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+impl<'a> Parsable<'a> for RplFileInfo {
+    fn try_parse<R: BinaryRead<'a>>(reader: &mut R) -> ParseResult<RplFileInfo> {
+        let start = reader.offset();
+        let version = reader.read_u32()?;
+        if version != VERSION {
+            return Err(reader.error_at(
+                ParseErrorKind::InvalidData(format!("unsupported version {:#x}", version)),
+                start,
+                4,
+            ));
+        }
+        let mut ret = RplFileInfo {
+            version,
+            text_size: reader.read_u32()?,
+            text_align: reader.read_u32()?,
+            data_size: reader.read_u32()?,
+            data_align: reader.read_u32()?,
+            load_size: reader.read_u32()?,
+            load_align: reader.read_u32()?,
+            temp_size: reader.read_u32()?,
+            tramp_adjust: reader.read_u32()?,
+            sda_base: reader.read_u32()?,
+            sda2_base: reader.read_u32()?,
+            stack_size: reader.read_u32()?,
+            filename_offset: reader.read_u32()?,
+            flags: RplFileFlags::try_parse(reader)?,
+            heap_size: reader.read_u32()?,
+            tag_offset: reader.read_u32()?,
+            min_version: reader.read_u32()?,
+            compression_level: reader.read_i32()?,
+            tramp_addition: reader.read_u32()?,
+            file_info_pad: reader.read_u32()?,
+            cafe_sdk_version: reader.read_u32()?,
+            cafe_sdk_revision: reader.read_u32()?,
+            tls_module_index: reader.read_u16()?,
+            tls_align_shift: reader.read_u16()?,
+            runtime_file_info_size: reader.read_u32()?,
+            ..Default::default()
+        };
+        let end = reader.offset();
+
+        if ret.filename_offset != 0 {
+            reader.seek(start + ret.filename_offset as usize);
+            ret.filename = Some(reader.scoped("filename", NulTerminated::try_parse)?.0);
+        }
+        if ret.tag_offset != 0 {
+            reader.seek(start + ret.tag_offset as usize);
+            reader.scoped("tags", |reader| {
+                loop {
+                    let key = NulTerminated::try_parse(reader)?.0;
+                    if key.is_empty() {
+                        break;
+                    }
+                    let value = NulTerminated::try_parse(reader)?.0;
+                    ret.tags.push((key, value));
+                }
+                Ok(())
+            })?;
+        }
+
+        reader.seek(end);
+        Ok(ret)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RplFileInfo;
+    use crate::binary_reader::{BinaryReader, Parsable, ParseErrorKind};
+
+    #[test]
+    fn test_parse() {
+        let mut data = Vec::new();
+        for value in [
+            0xcafe_0402u32,
+            0x0010_0000,
+            0x20,
+            0x0002_0000,
+            0x1000,
+            0x4000,
+            0x20,
+            0,
+            0,
+            0x1000_8000,
+            0x0200_8000,
+            0x0002_0000,
+            0x60,
+            0x2,
+            0x0100_0000,
+            0x6b,
+            0x5078,
+            6,
+            0,
+            0,
+            21204,
+            0xcafe,
+        ] {
+            data.extend_from_slice(&value.to_be_bytes());
+        }
+        data.extend_from_slice(&[0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00]);
+        data.extend_from_slice(b"game.rpx\0\0\0");
+        data.extend_from_slice(b"sdkver\x0021204\0bits\x00x\0\0");
+
+        let info = RplFileInfo::parse(&mut BinaryReader::new(&data));
+        assert_eq!(info.version, 0xcafe_0402);
+        assert_eq!(info.text_size, 0x0010_0000);
+        assert_eq!(info.stack_size, 0x0002_0000);
+        assert!(info.is_rpx());
+        assert_eq!(info.compression_level, 6);
+        assert_eq!(info.cafe_sdk_version, 21204);
+        assert_eq!(info.tls_module_index, 1);
+        assert_eq!(info.filename.as_deref(), Some("game.rpx"));
+        assert_eq!(info.tags.len(), 2);
+        assert_eq!(info.tag("sdkver"), Some("21204"));
+        assert_eq!(info.tag("bits"), Some("x"));
+
+        // an older layout is not misread as this one
+        data[3] = 0x01;
+        let error = RplFileInfo::try_parse(&mut BinaryReader::new(&data)).unwrap_err();
+        assert_eq!(
+            error.kind,
+            ParseErrorKind::InvalidData("unsupported version 0xcafe0401".to_string())
+        );
+        assert_eq!((error.offset, error.length), (0, 4));
+    }
+}
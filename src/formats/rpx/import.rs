@@ -0,0 +1,55 @@
+use crate::binary_reader::{BinaryRead, Parsable, ParseResult};
+use crate::formats::strings::NulTerminated;
+
+/// Whether an import or export section is for functions (`.fimport_*`,
+/// `.fexports`) or data (`.dimport_*`, `.dexports`), as told by its
+/// `EXECINSTR` flag.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum RplKind {
+    Function,
+    Data,
+}
+
+/// A `SHT_RPL_IMPORTS` section. The imported symbols are the ones defined in
+/// it, whose values are the addresses the loader patches.
+#[derive(PartialEq, Debug, Clone)]
+pub struct RplImport {
+    pub count: u32,
+    pub signature: u32,
+    /// Name of the library, e.g. `coreinit`.
+    pub library: String,
+
+    /// Index of the import section.
+    pub section: usize,
+    pub kind: RplKind,
+    /// Indices into [`Rpx::symbols`](super::Rpx::symbols).
+    pub symbols: Vec<usize>,
+}
+
+impl<'a> Parsable<'a> for RplImport {
+    fn try_parse<R: BinaryRead<'a>>(reader: &mut R) -> ParseResult<RplImport> {
+        Ok(RplImport {
+            count: reader.read_u32()?,
+            signature: reader.read_u32()?,
+            library: reader.scoped("library", NulTerminated::try_parse)?.0,
+            section: 0,
+            kind: RplKind::Function,
+            symbols: Vec::new(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RplImport;
+    use crate::binary_reader::{BinaryReader, Parsable};
+
+    #[test]
+    fn test_parse() {
+        let data = b"\x00\x00\x00\x02\x12\x34\x56\x78coreinit\0\0\0\0";
+        let import = RplImport::parse(&mut BinaryReader::new(data));
+        assert_eq!(import.count, 2);
+        assert_eq!(import.signature, 0x1234_5678);
+        assert_eq!(import.library, "coreinit");
+    }
+}
@@ -1,7 +1,20 @@
+pub mod crc;
+pub mod demangle;
+pub mod elf;
 pub mod elf_header;
 pub mod elf_identifier;
+pub mod export;
+pub mod file_info;
+pub mod import;
 pub mod program_header;
+pub mod relocation;
+#[allow(clippy::module_inception)]
 pub mod rpx;
+pub mod section_content;
 pub mod section_header;
+pub mod symbol;
+pub mod symbol_index;
+#[cfg(test)]
+mod test_elf;
 
 pub use rpx::Rpx;
@@ -1,4 +1,4 @@
-use crate::binary_reader::BinaryReader;
+use crate::binary_reader::{BinaryRead, Parsable, ParseResult};
 
 pub struct ProgramHeader {
     pub ph_type: u64,
@@ -41,41 +41,49 @@ impl ProgramHeader {
             ph_align: 0,
         }
     }
+}
 
-    pub fn parse(reader: &mut BinaryReader) -> ProgramHeader {
-        if reader.is_64bit {
+impl Default for ProgramHeader {
+    fn default() -> ProgramHeader {
+        ProgramHeader::new()
+    }
+}
+
+impl<'a> Parsable<'a> for ProgramHeader {
+    fn try_parse<R: BinaryRead<'a>>(reader: &mut R) -> ParseResult<ProgramHeader> {
+        Ok(if reader.is_64bit() {
             ProgramHeader {
-                ph_type: reader.read_word(),
-                ph_flags: reader.read_word(),
-                offset: reader.read_word(),
-                virtual_address: reader.read_word(),
-                physical_address: reader.read_word(),
-                file_size: reader.read_word(),
-                mem_size: reader.read_word(),
-                ph_align: reader.read_word(),
+                ph_type: reader.read_word()?,
+                ph_flags: reader.read_word()?,
+                offset: reader.read_word()?,
+                virtual_address: reader.read_word()?,
+                physical_address: reader.read_word()?,
+                file_size: reader.read_word()?,
+                mem_size: reader.read_word()?,
+                ph_align: reader.read_word()?,
             }
         } else {
             ProgramHeader {
-                ph_type: reader.read_word(),
-                offset: reader.read_word(),
-                virtual_address: reader.read_word(),
-                physical_address: reader.read_word(),
-                file_size: reader.read_word(),
-                mem_size: reader.read_word(),
-                ph_flags: reader.read_word(),
-                ph_align: reader.read_word(),
+                ph_type: reader.read_word()?,
+                offset: reader.read_word()?,
+                virtual_address: reader.read_word()?,
+                physical_address: reader.read_word()?,
+                file_size: reader.read_word()?,
+                mem_size: reader.read_word()?,
+                ph_flags: reader.read_word()?,
+                ph_align: reader.read_word()?,
             }
-        }
+        })
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::binary_reader::BinaryReader;
+    use crate::binary_reader::{BinaryReader, Parsable};
 
     #[test]
     fn test_parse() {
-        let mut reader = BinaryReader::new(vec![
+        let mut reader = BinaryReader::new(&[
             0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b, 0x0c, 0x0d,
             0x0e, 0x0f, 0x10, 0x11, 0x12, 0x13, 0x14, 0x15, 0x16, 0x17, 0x18, 0x19, 0x1a, 0x1b,
             0x1c, 0x1d, 0x1e, 0x1f,
@@ -0,0 +1,149 @@
+use crate::binary_reader::{BinaryRead, Parsable, ParseResult};
+
+macro_rules! relocation_types {
+    ($($(#[$meta:meta])* $name:ident = $value:expr,)*) => {
+        /// PowerPC relocation types found in RPL files, including the Green
+        /// Hills and Diab extensions Cafe OS supports.
+        #[allow(non_camel_case_types)]
+        #[derive(PartialEq, Eq, Debug, Clone, Copy, Hash)]
+        pub enum RelocationType {
+            $($(#[$meta])* $name,)*
+            Other(u32),
+        }
+
+        impl From<u32> for RelocationType {
+            fn from(value: u32) -> RelocationType {
+                match value {
+                    $($value => RelocationType::$name,)*
+                    _ => RelocationType::Other(value),
+                }
+            }
+        }
+
+        impl From<RelocationType> for u32 {
+            fn from(value: RelocationType) -> u32 {
+                match value {
+                    $(RelocationType::$name => $value,)*
+                    RelocationType::Other(value) => value,
+                }
+            }
+        }
+    };
+}
+
+relocation_types! {
+    NONE = 0,
+    ADDR32 = 1,
+    ADDR24 = 2,
+    ADDR16 = 3,
+    ADDR16_LO = 4,
+    ADDR16_HI = 5,
+    /// High half adjusted for the sign of the low half, as used by `lis`.
+    ADDR16_HA = 6,
+    ADDR14 = 7,
+    REL24 = 10,
+    REL14 = 11,
+    REL32 = 26,
+    DTPMOD32 = 68,
+    DTPREL32 = 78,
+    EMB_SDA21 = 109,
+    EMB_RELSDA = 116,
+    DIAB_SDA21_LO = 180,
+    DIAB_SDA21_HI = 181,
+    DIAB_SDA21_HA = 182,
+    DIAB_RELSDA_LO = 183,
+    DIAB_RELSDA_HI = 184,
+    DIAB_RELSDA_HA = 185,
+    GHS_REL16_HA = 251,
+    GHS_REL16_HI = 252,
+    GHS_REL16_LO = 253,
+}
+
+/// An entry of a `SHT_RELA` section. `section` and `symbol` are resolved by
+/// [`Rpx`](super::Rpx) from the `sh_info` and `sh_link` of the relocation
+/// section.
+#[derive(PartialEq, Debug, Clone)]
+pub struct Relocation {
+    /// Address of the patched location.
+    pub offset: u64,
+    pub info: u64,
+    pub addend: i64,
+    /// Index of the symbol in the linked symbol table, from `info`.
+    pub symbol_index: usize,
+    pub relocation_type: RelocationType,
+
+    /// Index of the section being patched.
+    pub section: usize,
+    /// Index into [`Rpx::symbols`](super::Rpx::symbols), or `None` for
+    /// symbol 0.
+    pub symbol: Option<usize>,
+}
+
+impl Relocation {
+    pub fn new(offset: u64, info: u64, addend: i64, is_64bit: bool) -> Relocation {
+        let (symbol_index, relocation_type) = if is_64bit {
+            (info >> 32, info as u32)
+        } else {
+            (info >> 8, info as u32 & 0xff)
+        };
+        Relocation {
+            offset,
+            info,
+            addend,
+            symbol_index: symbol_index as usize,
+            relocation_type: RelocationType::from(relocation_type),
+            section: 0,
+            symbol: None,
+        }
+    }
+
+    /// Size of one entry in the current ELF class.
+    pub fn entry_size(is_64bit: bool) -> usize {
+        if is_64bit {
+            24
+        } else {
+            12
+        }
+    }
+}
+
+impl<'a> Parsable<'a> for Relocation {
+    fn try_parse<R: BinaryRead<'a>>(reader: &mut R) -> ParseResult<Relocation> {
+        Ok(if reader.is_64bit() {
+            Relocation::new(
+                reader.read_u64()?,
+                reader.read_u64()?,
+                reader.read_i64()?,
+                true,
+            )
+        } else {
+            Relocation::new(
+                reader.read_u32()? as u64,
+                reader.read_u32()? as u64,
+                reader.read_i32()? as i64,
+                false,
+            )
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Relocation, RelocationType};
+    use crate::binary_reader::{BinaryReader, Parsable};
+
+    #[test]
+    fn test_parse() {
+        let data = vec![
+            0x02, 0x00, 0x01, 0x02, 0x00, 0x00, 0x05, 0x06, 0xff, 0xff, 0xff, 0xfc,
+        ];
+        let relocation = Relocation::parse(&mut BinaryReader::new(&data));
+        assert_eq!(relocation.offset, 0x0200_0102);
+        assert_eq!(relocation.symbol_index, 5);
+        assert_eq!(relocation.relocation_type, RelocationType::ADDR16_HA);
+        assert_eq!(relocation.addend, -4);
+
+        assert_eq!(RelocationType::from(253), RelocationType::GHS_REL16_LO);
+        assert_eq!(u32::from(RelocationType::from(200)), 200);
+    }
+}
@@ -1,29 +1,64 @@
+use super::crc::{section_crc, SectionCrc};
 use super::elf_header::ELFHeader;
+use super::export::{ExportIndex, RplExport, RplExports};
+use super::file_info::RplFileInfo;
+use super::import::{RplImport, RplKind};
 use super::program_header::ProgramHeader;
-use super::section_header::{SectionHeader, SectionName};
-use crate::binary_reader::BinaryReader;
-use crate::utils::find_zero;
+use super::relocation::Relocation;
+use super::section_content::SectionContent;
+use super::section_header::{SectionFlags, SectionHeader, SectionKind, SectionName};
+use super::symbol::Symbol;
+use super::symbol_index::SymbolIndex;
+use crate::binary_reader::{
+    AddressSpace, BinaryRead, BinaryReader, DiagnosticCode, DiagnosticMode, Diagnostics, Parsable,
+    ParseErrorKind, ParseResult, Span,
+};
+use crate::binary_writer::BinaryWriter;
+use std::borrow::Cow;
+use std::cell::OnceCell;
+use std::collections::HashMap;
+use std::ops::Range;
 
-pub struct Rpx {
+macro_rules! read_at_vaddr {
+    ($($name:ident: $ty:ty => $read:ident;)*) => {
+        $(
+            pub fn $name(&self, address: u64) -> ParseResult<$ty> {
+                let data = self.read_vaddr(address, std::mem::size_of::<$ty>())?;
+                let mut reader = BinaryReader::new(&data);
+                reader.endian = self.reader.endian;
+                reader.$read()
+            }
+        )*
+    };
+}
+
+#[derive(Default)]
+pub struct Rpx<'a> {
     pub elf_header: ELFHeader,
     pub program_headers: Vec<ProgramHeader>,
-    pub section_headers: Vec<SectionHeader>,
-    pub reader: BinaryReader,
-}
+    pub section_headers: Vec<SectionHeader<'a>>,
+    /// Entries of every `SHT_SYMTAB` section, in section order.
+    pub symbols: Vec<Symbol>,
+    /// Entries of every `SHT_RELA` section, in section order.
+    pub relocations: Vec<Relocation>,
+    /// Every `SHT_RPL_IMPORTS` section, in section order.
+    pub imports: Vec<RplImport>,
+    /// Every `SHT_RPL_EXPORTS` section, in section order.
+    pub exports: Vec<RplExports>,
+    /// Contents of the `SHT_RPL_CRCS` section, one CRC per section.
+    pub crcs: Vec<u32>,
+    pub reader: BinaryReader<'a>,
 
-impl Default for Rpx {
-    fn default() -> Self {
-        Self {
-            elf_header: ELFHeader::default(),
-            program_headers: Vec::new(),
-            section_headers: Vec::new(),
-            reader: BinaryReader::default(),
-        }
-    }
+    /// Section index of each symbol table and its entries in `symbols`.
+    symbol_tables: Vec<(usize, Range<usize>)>,
+    relocation_tables: Vec<(usize, Range<usize>)>,
+    file_info: Option<RplFileInfo>,
+    symbol_index: OnceCell<SymbolIndex>,
+    export_index: OnceCell<ExportIndex>,
 }
 
-impl Rpx {
-    pub fn parse(reader: BinaryReader) -> Rpx {
+impl<'a> Rpx<'a> {
+    pub fn try_parse(reader: BinaryReader<'a>) -> ParseResult<Rpx<'a>> {
         let mut ret = {
             Self {
                 reader,
@@ -31,47 +66,1270 @@ impl Rpx {
             }
         };
 
-        ret.init();
+        ret.init()?;
 
+        Ok(ret)
+    }
+
+    /// Panicking counterpart of [`Rpx::try_parse`].
+    pub fn parse(reader: BinaryReader<'a>) -> Rpx<'a> {
+        Rpx::try_parse(reader).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    pub fn init(&mut self) -> ParseResult<()> {
+        self.reader.path.push("Rpx".to_string());
+        let ret = self.init_headers();
+        self.reader.path.pop();
         ret
     }
 
-    pub fn init(&mut self) {
-        self.elf_header = ELFHeader::parse(&mut self.reader);
+    fn init_headers(&mut self) -> ParseResult<()> {
+        self.elf_header = self.reader.scoped("ELFHeader", ELFHeader::try_parse)?;
 
         self.reader
             .seek(self.elf_header.program_header_offset as usize);
-        for _ in 0..self.elf_header.program_headers_count {
-            self.program_headers
-                .push(ProgramHeader::parse(&mut self.reader));
+        for i in 0..self.elf_header.program_headers_count {
+            self.program_headers.push(
+                self.reader
+                    .scoped(format!("ProgramHeader[{}]", i), ProgramHeader::try_parse)?,
+            );
         }
 
         self.reader
             .seek(self.elf_header.section_header_offset as usize);
-        for _ in 0..self.elf_header.section_header_count {
-            self.section_headers
-                .push(SectionHeader::parse(&mut self.reader));
+        for i in 0..self.elf_header.section_header_count {
+            self.section_headers.push(
+                self.reader
+                    .scoped(format!("SectionHeader[{}]", i), SectionHeader::try_parse)?,
+            );
         }
 
         for i in 0..self.section_headers.len() {
             if let SectionName::Offset(offset) = self.section_headers[i].name {
-                self.section_headers[i].name =
-                    SectionName::String(self.read_str_from_strtab(offset));
+                let name = self
+                    .reader
+                    .scoped(format!("SectionHeader[{}]", i), |reader| {
+                        reader.scoped("name", |reader| {
+                            Rpx::str_from_strtab(
+                                reader,
+                                &self.section_headers,
+                                self.elf_header.str_table_index as usize,
+                                offset,
+                            )
+                        })
+                    })?;
+                self.section_headers[i].name = SectionName::String(name);
             }
         }
+
+        self.init_symbols()?;
+        let result = self.init_relocations();
+        self.recover(result)?;
+        let result = self.init_imports();
+        self.recover(result)?;
+        let result = self.init_exports();
+        self.recover(result)?;
+        let result = self.init_file_info();
+        self.recover(result)?;
+        self.init_crcs()
+    }
+
+    /// Reports the failure to decode a secondary table, such as the imports,
+    /// as a warning at the place it happened and leaves that table empty, so
+    /// that the rest of the file stays usable. Strict mode fails with the
+    /// original error instead.
+    fn recover(&mut self, result: ParseResult<()>) -> ParseResult<()> {
+        let Err(error) = result else {
+            return Ok(());
+        };
+        if self.reader.diagnostics.mode == DiagnosticMode::Strict {
+            return Err(error);
+        }
+        let path = std::mem::replace(&mut self.reader.path, error.path);
+        let ret = self.reader.warn_at(
+            DiagnosticCode::BadSectionContents,
+            error.offset.saturating_sub(self.reader.base),
+            error.length,
+            error.kind,
+        );
+        self.reader.path = path;
+        ret
+    }
+
+    fn init_symbols(&mut self) -> ParseResult<()> {
+        let mut symbols = Vec::new();
+        let mut symbol_tables = Vec::new();
+        let mut spans = Vec::new();
+        for (i, section) in self.section_headers.iter().enumerate() {
+            if section.kind() != SectionKind::SymTab {
+                continue;
+            }
+            let strtab = section.sh_link as usize;
+            let entries = self.read_entries(
+                i,
+                Symbol::entry_size(self.reader.is_64bit),
+                "Symbol",
+                &mut spans,
+                |reader| {
+                    let mut symbol = Symbol::try_parse(reader)?;
+                    symbol.name = reader.scoped("name", |reader| {
+                        Rpx::str_from_strtab(
+                            reader,
+                            &self.section_headers,
+                            strtab,
+                            symbol.name_offset as usize,
+                        )
+                    })?;
+                    Ok(symbol)
+                },
+            )?;
+            let start = symbols.len();
+            symbols.extend(entries);
+            symbol_tables.push((i, start..symbols.len()));
+        }
+        self.symbols = symbols;
+        self.symbol_tables = symbol_tables;
+        for (i, spans) in spans {
+            self.merge_spans(i, spans);
+        }
+        Ok(())
+    }
+
+    fn init_relocations(&mut self) -> ParseResult<()> {
+        let mut relocations = Vec::new();
+        let mut relocation_tables = Vec::new();
+        let mut spans = Vec::new();
+        for (i, section) in self.section_headers.iter().enumerate() {
+            if section.kind() != SectionKind::Rela {
+                continue;
+            }
+            let target = section.sh_info as usize;
+            let symbols = Rpx::table_range(&self.symbol_tables, section.sh_link as usize);
+            let entries = self.read_entries(
+                i,
+                Relocation::entry_size(self.reader.is_64bit),
+                "Relocation",
+                &mut spans,
+                |reader| {
+                    let mut relocation = Relocation::try_parse(reader)?;
+                    if target >= self.section_headers.len() {
+                        return Err(
+                            reader.invalid_data(format!("target section {} out of range", target))
+                        );
+                    }
+                    relocation.section = target;
+                    if relocation.symbol_index != 0 {
+                        let index = relocation.symbol_index;
+                        let symbols = symbols
+                            .clone()
+                            .filter(|symbols| index < symbols.len())
+                            .ok_or_else(|| {
+                                reader.invalid_data(format!("symbol {} out of range", index))
+                            })?;
+                        relocation.symbol = Some(symbols.start + index);
+                    }
+                    Ok(relocation)
+                },
+            )?;
+            let start = relocations.len();
+            relocations.extend(entries);
+            relocation_tables.push((i, start..relocations.len()));
+        }
+        self.relocations = relocations;
+        self.relocation_tables = relocation_tables;
+        for (i, spans) in spans {
+            self.merge_spans(i, spans);
+        }
+        Ok(())
+    }
+
+    fn init_imports(&mut self) -> ParseResult<()> {
+        let mut imports = Vec::new();
+        let mut spans = Vec::new();
+        // symbol indices by the section they are defined in
+        let mut by_section = HashMap::<usize, Vec<usize>>::new();
+        for (j, symbol) in self.symbols.iter().enumerate() {
+            by_section.entry(symbol.shndx as usize).or_default().push(j);
+        }
+        for (i, section) in self.section_headers.iter().enumerate() {
+            if section.kind() != SectionKind::RplImports {
+                continue;
+            }
+            let mut reader = self.section_reader(i);
+            let mut import = reader.scoped(format!("SectionHeader[{}]", i), |reader| {
+                reader.scoped("RplImport", RplImport::try_parse)
+            })?;
+            spans.push((i, reader.spans.take()));
+            import.section = i;
+            import.kind = Rpx::rpl_kind(section);
+            import.symbols = by_section.remove(&i).unwrap_or_default();
+            imports.push(import);
+        }
+        self.imports = imports;
+        for (i, spans) in spans {
+            self.merge_spans(i, spans);
+        }
+        Ok(())
+    }
+
+    fn init_exports(&mut self) -> ParseResult<()> {
+        let mut exports = Vec::new();
+        let mut spans = Vec::new();
+        for (i, section) in self.section_headers.iter().enumerate() {
+            if section.kind() != SectionKind::RplExports {
+                continue;
+            }
+            let mut reader = self.section_reader(i);
+            let mut export =
+                reader.scoped(format!("SectionHeader[{}]", i), RplExports::try_parse)?;
+            spans.push((i, reader.spans.take()));
+            export.section = i;
+            export.kind = Rpx::rpl_kind(section);
+            exports.push(export);
+        }
+        self.exports = exports;
+        for (i, spans) in spans {
+            self.merge_spans(i, spans);
+        }
+        Ok(())
+    }
+
+    fn init_file_info(&mut self) -> ParseResult<()> {
+        let Some(i) = self.file_info_section() else {
+            return Ok(());
+        };
+        let mut reader = self.section_reader(i);
+        let file_info = reader.scoped(format!("SectionHeader[{}]", i), |reader| {
+            reader.scoped("RplFileInfo", RplFileInfo::try_parse)
+        })?;
+        let spans = reader.spans.take();
+        self.file_info = Some(file_info);
+        self.merge_spans(i, spans);
+        Ok(())
+    }
+
+    fn init_crcs(&mut self) -> ParseResult<()> {
+        let Some(i) = self.crc_section() else {
+            return Ok(());
+        };
+        let mut reader = self.section_reader(i);
+        let count = reader.len() / 4;
+        let crcs = reader.scoped(format!("SectionHeader[{}]", i), |reader| {
+            reader.scoped("crcs", |reader| reader.read_u32_array(count))
+        })?;
+        let spans = reader.spans.take();
+        self.crcs = crcs;
+        self.merge_spans(i, spans);
+        Ok(())
+    }
+
+    fn file_info_section(&self) -> Option<usize> {
+        self.section_headers
+            .iter()
+            .position(|section| section.kind() == SectionKind::RplFileInfo)
+    }
+
+    fn crc_section(&self) -> Option<usize> {
+        self.section_headers
+            .iter()
+            .position(|section| section.kind() == SectionKind::RplCrcs)
+    }
+
+    fn rpl_kind(section: &SectionHeader) -> RplKind {
+        if section.flags().contains(SectionFlags::EXECINSTR) {
+            RplKind::Function
+        } else {
+            RplKind::Data
+        }
+    }
+
+    /// Parses the fixed-size entries of section `index` with `f`, each scoped
+    /// as `{label}[j]`. Entries are `sh_entsize` apart, or `default_size` if
+    /// that is 0.
+    fn read_entries<T, F>(
+        &self,
+        index: usize,
+        default_size: usize,
+        label: &str,
+        spans: &mut Vec<(usize, Option<Vec<Span>>)>,
+        mut f: F,
+    ) -> ParseResult<Vec<T>>
+    where
+        F: FnMut(&mut BinaryReader<'_>) -> ParseResult<T>,
+    {
+        let section = &self.section_headers[index];
+        let mut reader = self.section_reader(index);
+        let entry_size = match section.sh_ent_size as usize {
+            0 => default_size,
+            size => size,
+        };
+        let count = reader.check_count::<T>(section.data.len() / entry_size)?;
+        let ret = reader.scoped(format!("SectionHeader[{}]", index), |reader| {
+            (0..count)
+                .map(|j| {
+                    reader.seek(j * entry_size);
+                    reader.scoped(format!("{}[{}]", label, j), &mut f)
+                })
+                .collect()
+        })?;
+        spans.push((index, reader.spans.take()));
+        Ok(ret)
+    }
+
+    /// Moves the spans recorded while decoding section `index` into
+    /// `self.reader`, in place of the span of its raw contents, so that the
+    /// coverage report shows which bytes of the section are still unknown.
+    fn merge_spans(&mut self, index: usize, spans: Option<Vec<Span>>) {
+        let (Some(all), Some(spans)) = (&mut self.reader.spans, spans) else {
+            return;
+        };
+        if spans.is_empty() {
+            return;
+        }
+        let section = format!("SectionHeader[{}]", index);
+        all.retain(|span| !span.path.ends_with(&[section.clone(), "data".to_string()]));
+        all.extend(spans);
+    }
+
+    fn table_range(tables: &[(usize, Range<usize>)], index: usize) -> Option<Range<usize>> {
+        tables
+            .iter()
+            .find(|(section, _)| *section == index)
+            .map(|(_, range)| range.clone())
     }
 }
 
-impl Rpx {
-    pub fn read_str_from_strtab(&mut self, offset: usize) -> String {
-        let data = &self.section_headers[self.elf_header.str_table_index as usize].data;
-        let end = find_zero(data.to_vec(), offset);
-        if offset == end {
-            return String::new();
+impl<'a> Rpx<'a> {
+    /// A reader over the contents of section `index`, in the byte order and
+    /// class of the file. Offsets in its errors and spans are absolute unless
+    /// the section was inflated, in which case no spans are recorded. Panics
+    /// if `index` is out of range.
+    pub fn section_reader(&self, index: usize) -> BinaryReader<'_> {
+        let section = &self.section_headers[index];
+        BinaryReader {
+            data: &section.data,
+            offset: 0,
+            endian: self.reader.endian,
+            is_64bit: self.reader.is_64bit,
+            path: self.reader.path.clone(),
+            diagnostics: Diagnostics::new(self.reader.diagnostics.mode),
+            limits: self.reader.limits,
+            spans: match (&self.reader.spans, &section.data) {
+                (Some(_), Cow::Borrowed(_)) => Some(Vec::new()),
+                _ => None,
+            },
+            base: match section.data {
+                Cow::Borrowed(_) => section.offset as usize,
+                Cow::Owned(_) => 0,
+            },
+        }
+    }
+
+    /// The decoded contents of section `index`. Sections of a known kind whose
+    /// contents were not decoded, such as a second `SHT_RPL_FILEINFO`, are
+    /// returned as [`SectionContent::Bytes`].
+    pub fn section_content(&self, index: usize) -> Option<SectionContent<'_>> {
+        let section = self.section_headers.get(index)?;
+        let decoded = match section.kind() {
+            SectionKind::Null => Some(SectionContent::Null),
+            SectionKind::NoBits => Some(SectionContent::NoBits(section.size)),
+            SectionKind::StrTab => Some(SectionContent::Strings(&section.data)),
+            SectionKind::SymTab => self.symbol_table(index).map(SectionContent::Symbols),
+            SectionKind::Rela => self
+                .relocation_table(index)
+                .map(SectionContent::Relocations),
+            SectionKind::RplExports => self
+                .exports
+                .iter()
+                .find(|exports| exports.section == index)
+                .map(SectionContent::Exports),
+            SectionKind::RplImports => self
+                .imports
+                .iter()
+                .find(|import| import.section == index)
+                .map(SectionContent::Import),
+            SectionKind::RplCrcs if self.crc_section() == Some(index) => {
+                Some(SectionContent::Crcs(&self.crcs))
+            }
+            SectionKind::RplFileInfo if self.file_info_section() == Some(index) => {
+                self.file_info().map(SectionContent::FileInfo)
+            }
+            _ => None,
+        };
+        Some(decoded.unwrap_or(SectionContent::Bytes(&section.data)))
+    }
+
+    /// Entries of the symbol table in section `index`.
+    pub fn symbol_table(&self, index: usize) -> Option<&[Symbol]> {
+        Some(&self.symbols[Rpx::table_range(&self.symbol_tables, index)?])
+    }
+
+    /// Entries of the relocation section `index`.
+    pub fn relocation_table(&self, index: usize) -> Option<&[Relocation]> {
+        Some(&self.relocations[Rpx::table_range(&self.relocation_tables, index)?])
+    }
+
+    pub fn relocation_symbol(&self, relocation: &Relocation) -> Option<&Symbol> {
+        self.symbols.get(relocation.symbol?)
+    }
+
+    /// Names of the libraries imported from, sorted and without duplicates.
+    pub fn imported_libraries(&self) -> Vec<&str> {
+        let mut ret = self
+            .imports
+            .iter()
+            .map(|import| import.library.as_str())
+            .collect::<Vec<_>>();
+        ret.sort_unstable();
+        ret.dedup();
+        ret
+    }
+
+    /// Lookup tables over `exports`, built on first use. Changes made to
+    /// `exports` afterwards are not reflected.
+    pub fn export_index(&self) -> &ExportIndex {
+        self.export_index
+            .get_or_init(|| ExportIndex::new(&self.exports))
+    }
+
+    /// The export named `name`, from any export section.
+    pub fn export_by_name(&self, name: &str) -> Option<&RplExport> {
+        let (i, j) = self.export_index().by_name(name)?;
+        Some(&self.exports[i].entries[j])
+    }
+
+    /// The export whose value is `address`.
+    pub fn export_at(&self, address: u64) -> Option<&RplExport> {
+        let (i, j) = self.export_index().at_address(address)?;
+        Some(&self.exports[i].entries[j])
+    }
+
+    /// CRCs of the current section contents, as the `SHT_RPL_CRCS` section
+    /// should hold them. The entry for that section itself is 0.
+    pub fn compute_crcs(&self) -> Vec<u32> {
+        let crc_section = self.crc_section();
+        self.section_headers
+            .iter()
+            .enumerate()
+            .map(|(i, section)| {
+                if Some(i) == crc_section {
+                    0
+                } else {
+                    section_crc(&section.data)
+                }
+            })
+            .collect()
+    }
+
+    /// Compares `crcs` with the CRCs of the section contents, with one row
+    /// per section or stored CRC, whichever there are more of. Empty if the
+    /// file has no `SHT_RPL_CRCS` section.
+    pub fn verify_crcs(&self) -> Vec<SectionCrc> {
+        if self.crc_section().is_none() {
+            return Vec::new();
+        }
+        let actual = self.compute_crcs();
+        (0..self.crcs.len().max(actual.len()))
+            .map(|section| SectionCrc {
+                section,
+                expected: self.crcs.get(section).copied(),
+                actual: actual.get(section).copied(),
+            })
+            .collect()
+    }
+
+    /// Recomputes `crcs` after section contents were edited, and stores them
+    /// as the (uncompressed) contents of the `SHT_RPL_CRCS` section.
+    pub fn update_crcs(&mut self) {
+        self.crcs = self.compute_crcs();
+        let Some(i) = self.crc_section() else {
+            return;
+        };
+        let mut writer = BinaryWriter::new();
+        writer.endian = self.reader.endian;
+        for &crc in &self.crcs {
+            writer.write_u32(crc);
+        }
+        let section = &mut self.section_headers[i];
+        let data = writer.into_inner();
+        section.size = data.len() as u64;
+        section.sh_flags &= !SectionFlags::RPL_ZLIB.bits();
+        section.remained_flags = section.sh_flags;
+        section.data = Cow::Owned(data);
+    }
+
+    /// The decoded `SHT_RPL_FILEINFO` section, if the file has one.
+    pub fn file_info(&self) -> Option<&RplFileInfo> {
+        self.file_info.as_ref()
+    }
+
+    /// Symbols imported through `import`. Their values are the addresses of
+    /// the stubs or pointers the loader fills in.
+    pub fn import_symbols<'s>(
+        &'s self,
+        import: &'s RplImport,
+    ) -> impl Iterator<Item = &'s Symbol> + 's {
+        import.symbols.iter().map(|&index| &self.symbols[index])
+    }
+
+    /// The bytes `symbol` covers in the section it is defined in, or `None`
+    /// for undefined and absolute symbols, `SHT_NOBITS` sections and values
+    /// outside the section.
+    pub fn symbol_data(&self, symbol: &Symbol) -> Option<&[u8]> {
+        if !symbol.has_section() {
+            return None;
+        }
+        let section = self.section_headers.get(symbol.shndx as usize)?;
+        if section.kind() == SectionKind::NoBits {
+            return None;
         }
-        String::from_utf8(data[offset..end].to_vec()).unwrap()
+        let start = usize::try_from(symbol.value.checked_sub(section.address)?).ok()?;
+        section
+            .data
+            .get(start..start.checked_add(symbol.size as usize)?)
+    }
+
+    /// Lookup tables over `symbols`, built on first use. Changes made to
+    /// `symbols` afterwards are not reflected.
+    pub fn symbol_index(&self) -> &SymbolIndex {
+        self.symbol_index
+            .get_or_init(|| SymbolIndex::new(&self.symbols))
+    }
+
+    /// The first symbol whose mangled or demangled name is `name`.
+    pub fn symbol_by_name(&self, name: &str) -> Option<&Symbol> {
+        let index = *self.symbol_index().by_name(name).first()?;
+        Some(&self.symbols[index])
+    }
+
+    /// The first symbol whose value is `address`.
+    pub fn symbol_at(&self, address: u64) -> Option<&Symbol> {
+        let index = *self.symbol_index().at_address(address).first()?;
+        Some(&self.symbols[index])
+    }
+
+    /// The smallest symbol whose range contains `address`.
+    pub fn symbol_containing(&self, address: u64) -> Option<&Symbol> {
+        let index = self.symbol_index().smallest_containing(address)?;
+        Some(&self.symbols[index])
+    }
+
+    /// See [`SymbolIndex::search`].
+    pub fn search_symbols(&self, class: Option<&str>, query: &str) -> Vec<&Symbol> {
+        self.symbol_index()
+            .search(class, query)
+            .into_iter()
+            .map(|index| &self.symbols[index])
+            .collect()
+    }
+}
+
+impl<'a> Rpx<'a> {
+    fn section_index_for_vaddr(&self, address: u64) -> Option<usize> {
+        self.section_headers.iter().position(|section| {
+            // sh_size is the compressed size of RPL_ZLIB sections
+            let loaded_size = match section.kind() {
+                SectionKind::NoBits => section.size,
+                _ => section.data.len() as u64,
+            };
+            section.flags().contains(SectionFlags::ALLOC)
+                && address >= section.address
+                && address - section.address < loaded_size
+        })
+    }
+
+    /// The allocated section whose address range contains `address`.
+    pub fn section_for_vaddr(&self, address: u64) -> Option<&SectionHeader<'a>> {
+        Some(&self.section_headers[self.section_index_for_vaddr(address)?])
+    }
+
+    /// Reads `len` bytes at virtual address `address` from the contents of the
+    /// section holding it, inflated if needed. `SHT_NOBITS` sections read as
+    /// zeros. The bytes may not cross into another section.
+    pub fn read_vaddr(&self, address: u64, len: usize) -> ParseResult<Cow<'_, [u8]>> {
+        let index = self
+            .section_index_for_vaddr(address)
+            .ok_or_else(|| self.reader.error(ParseErrorKind::BadPointer(address), len))?;
+        let section = &self.section_headers[index];
+        let start = (address - section.address) as usize;
+        if section.kind() == SectionKind::NoBits {
+            let available = section.size as usize - start;
+            if len > available {
+                return Err(self.reader.error_at(
+                    ParseErrorKind::UnexpectedEof { available },
+                    section.offset as usize + start,
+                    len,
+                ));
+            }
+            return Ok(Cow::Owned(vec![0; len]));
+        }
+        self.section_reader(index).check(start, len)?;
+        Ok(Cow::Borrowed(&section.data[start..start + len]))
+    }
+
+    read_at_vaddr! {
+        read_u8_at_vaddr: u8 => read_u8;
+        read_u16_at_vaddr: u16 => read_u16;
+        read_u32_at_vaddr: u32 => read_u32;
+        read_u64_at_vaddr: u64 => read_u64;
+        read_i8_at_vaddr: i8 => read_i8;
+        read_i16_at_vaddr: i16 => read_i16;
+        read_i32_at_vaddr: i32 => read_i32;
+        read_i64_at_vaddr: i64 => read_i64;
+        read_f32_at_vaddr: f32 => read_f32;
+        read_f64_at_vaddr: f64 => read_f64;
+    }
+
+    /// Maps the allocated sections stored uncompressed in the file, so that
+    /// [`Ptr`](crate::binary_reader::Ptr)s into them can be followed with
+    /// `reader`.
+    pub fn address_space(&self) -> AddressSpace {
+        self.section_headers
+            .iter()
+            .filter(|section| {
+                section.flags().contains(SectionFlags::ALLOC)
+                    && section.kind() != SectionKind::NoBits
+                    && matches!(section.data, Cow::Borrowed(_))
+            })
+            .fold(AddressSpace::default(), |space, section| {
+                space.with_range(section.address, section.offset as usize, section.size)
+            })
+    }
+
+    /// File offset of the byte at `address`, if it lies in a section of
+    /// [`Rpx::address_space`].
+    pub fn vaddr_to_offset(&self, address: u64) -> Option<usize> {
+        // not `AddressSpace::translate`, which maps everything when empty
+        self.address_space()
+            .ranges
+            .iter()
+            .find(|range| range.contains(address))
+            .map(|range| range.offset + (address - range.address) as usize)
+    }
+
+    /// Virtual address of the byte at file offset `offset`, if it lies in a
+    /// section of [`Rpx::address_space`].
+    pub fn offset_to_vaddr(&self, offset: usize) -> Option<u64> {
+        self.address_space()
+            .ranges
+            .iter()
+            .find(|range| offset >= range.offset && ((offset - range.offset) as u64) < range.size)
+            .map(|range| range.address + (offset - range.offset) as u64)
+    }
+}
+
+impl<'a> Rpx<'a> {
+    pub fn read_str_from_strtab(&mut self, offset: usize) -> ParseResult<String> {
+        Rpx::str_from_strtab(
+            &self.reader,
+            &self.section_headers,
+            self.elf_header.str_table_index as usize,
+            offset,
+        )
+    }
+
+    fn str_from_strtab(
+        reader: &BinaryReader,
+        section_headers: &[SectionHeader],
+        str_table_index: usize,
+        offset: usize,
+    ) -> ParseResult<String> {
+        let strtab = section_headers.get(str_table_index).ok_or_else(|| {
+            reader.invalid_data(format!(
+                "string table index {} out of range",
+                str_table_index
+            ))
+        })?;
+        let data = &strtab.data;
+        if data.is_empty() {
+            return Ok(String::new());
+        }
+        let end = data
+            .get(offset..)
+            .and_then(|rest| rest.iter().position(|&b| b == 0))
+            .map(|len| offset + len)
+            .ok_or_else(|| {
+                reader.error_at(
+                    ParseErrorKind::UnexpectedEof {
+                        available: data.len().saturating_sub(offset),
+                    },
+                    strtab.offset as usize + offset,
+                    1,
+                )
+            })?;
+        String::from_utf8(data[offset..end].to_vec()).map_err(|e| {
+            reader.error_at(
+                ParseErrorKind::InvalidData(e.to_string()),
+                strtab.offset as usize + offset,
+                end - offset,
+            )
+        })
     }
 }
 
 #[cfg(test)]
-mod tests {}
+mod tests {
+    use super::Rpx;
+    use crate::binary_reader::{BinaryRead, BinaryReader};
+    use crate::binary_reader::{DiagnosticCode, DiagnosticMode, ParseErrorKind};
+    use crate::formats::rpx::import::RplKind;
+    use crate::formats::rpx::relocation::RelocationType;
+    use crate::formats::rpx::section_content::SectionContent;
+    use crate::formats::rpx::section_header::{
+        SHT_NOBITS, SHT_RELA, SHT_RPL_CRCS, SHT_RPL_EXPORTS, SHT_RPL_FILEINFO, SHT_RPL_IMPORTS,
+        SHT_STRTAB, SHT_SYMTAB,
+    };
+    use crate::formats::rpx::symbol::{SymbolBinding, SymbolType, SHN_ABS};
+    use crate::formats::rpx::test_elf::{self, TestSection};
+    use flate2::write::ZlibEncoder;
+    use flate2::Compression;
+    use std::io::Write;
+
+    fn elf_header(section_header_count: u8) -> Vec<u8> {
+        vec![
+            0x7f,
+            0x45,
+            0x4c,
+            0x46,
+            0x01,
+            0x02,
+            0x01,
+            0xca,
+            0xfe,
+            0x00,
+            0x00,
+            0x00,
+            0x00,
+            0x00,
+            0x00,
+            0x00,
+            0xfe,
+            0x01,
+            0x00,
+            0x14,
+            0x00,
+            0x00,
+            0x00,
+            0x01,
+            0x02,
+            0x00,
+            0x00,
+            0x00,
+            0x00,
+            0x00,
+            0x00,
+            0x00,
+            0x00,
+            0x00,
+            0x00,
+            0x34,
+            0x00,
+            0x00,
+            0x00,
+            0x00,
+            0x00,
+            0x34,
+            0x00,
+            0x00,
+            0x00,
+            0x00,
+            0x00,
+            0x28,
+            0x00,
+            section_header_count,
+            0x00,
+            0x00,
+        ]
+    }
+
+    #[test]
+    fn test_parse() {
+        let mut data = elf_header(1);
+        // string table: "\0.shstrtab\0" right after the section header
+        data.extend_from_slice(&[
+            0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x03, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x5c, 0x00, 0x00, 0x00, 0x0b, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00,
+        ]);
+        data.extend_from_slice(b"\0.shstrtab\0");
+
+        let mut reader = BinaryReader::new(&data);
+        reader.record_spans();
+        let rpx = Rpx::try_parse(reader).unwrap();
+        assert_eq!(rpx.section_headers.len(), 1);
+        assert_eq!(rpx.section_headers[0].name.to_string(), ".shstrtab");
+
+        let report = rpx.reader.coverage().unwrap();
+        assert!(report.gaps.is_empty());
+        assert!(report.overlaps.is_empty());
+        assert_eq!(report.percent(), 100.0);
+    }
+
+    #[test]
+    fn test_truncated_section() {
+        let mut data = elf_header(1);
+        data.extend_from_slice(&[
+            0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x5c, 0x00, 0x00, 0x10, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00,
+        ]);
+
+        let error = Rpx::try_parse(BinaryReader::new(&data)).err().unwrap();
+        assert_eq!(error.offset, 0x5c);
+        assert_eq!(error.length, 0x1000);
+        assert_eq!(error.path_string(), "Rpx > SectionHeader[0] > data");
+    }
+
+    fn symbol(name: u32, value: u32, size: u32, info: u8, shndx: u16) -> Vec<u8> {
+        let mut ret = Vec::new();
+        ret.extend_from_slice(&name.to_be_bytes());
+        ret.extend_from_slice(&value.to_be_bytes());
+        ret.extend_from_slice(&size.to_be_bytes());
+        ret.extend_from_slice(&[info, 0]);
+        ret.extend_from_slice(&shndx.to_be_bytes());
+        ret
+    }
+
+    #[test]
+    fn test_symbols() {
+        let mut text = TestSection::new(".text", 1, (0..16).collect());
+        text.address = 0x0200_0000;
+        let mut symtab = TestSection::new(
+            ".symtab",
+            SHT_SYMTAB,
+            [
+                symbol(0, 0, 0, 0, 0),
+                symbol(1, 0x0200_0004, 8, 0x12, 1),
+                symbol(6, 0x1234, 0, 0x01, SHN_ABS),
+            ]
+            .concat(),
+        );
+        symtab.link = 3;
+        symtab.ent_size = 16;
+        let strtab = TestSection::new(".strtab", SHT_STRTAB, b"\0main\0abs\0".to_vec());
+        let data = test_elf::build(vec![text, symtab, strtab]);
+
+        let rpx = Rpx::try_parse(BinaryReader::new(&data)).unwrap();
+        assert_eq!(rpx.symbols.len(), 3);
+        assert_eq!(rpx.symbol_table(2).unwrap().len(), 3);
+        assert!(rpx.symbol_table(1).is_none());
+
+        let main = &rpx.symbols[1];
+        assert_eq!(main.name, "main");
+        assert_eq!(main.binding(), SymbolBinding::Global);
+        assert_eq!(main.symbol_type(), SymbolType::Func);
+        assert_eq!(rpx.symbol_data(main), Some(&[4, 5, 6, 7, 8, 9, 10, 11][..]));
+
+        assert_eq!(rpx.symbols[2].name, "abs");
+        assert_eq!(rpx.symbol_data(&rpx.symbols[2]), None);
+        assert_eq!(rpx.symbol_data(&rpx.symbols[0]), None);
+
+        assert_eq!(rpx.symbol_by_name("main"), Some(main));
+        assert_eq!(rpx.symbol_at(0x0200_0004), Some(main));
+        assert_eq!(rpx.symbol_containing(0x0200_000b), Some(main));
+        assert_eq!(rpx.symbol_containing(0x0200_000c), None);
+        assert_eq!(rpx.search_symbols(None, "A"), vec![&rpx.symbols[2], main]);
+    }
+
+    #[test]
+    fn test_section_spans() {
+        let mut symtab = TestSection::new(
+            ".symtab",
+            SHT_SYMTAB,
+            [symbol(0, 0, 0, 0, 0), symbol(1, 0x1234, 0, 0x01, SHN_ABS)].concat(),
+        );
+        symtab.link = 2;
+        let strtab = TestSection::new(".strtab", SHT_STRTAB, b"\0abs\0".to_vec());
+        let data = test_elf::build(vec![symtab, strtab]);
+        let symtab_offset = test_elf::HEADER_SIZE + 4 * test_elf::SECTION_HEADER_SIZE;
+
+        let mut reader = BinaryReader::new(&data);
+        reader.record_spans();
+        let rpx = Rpx::try_parse(reader).unwrap();
+        let spans = rpx.reader.spans.as_ref().unwrap();
+        let symbol = spans
+            .iter()
+            .find(|span| span.label() == "Rpx > SectionHeader[1] > Symbol[1]")
+            .unwrap();
+        assert_eq!(symbol.offset, symtab_offset + 16);
+        assert!(!spans
+            .iter()
+            .any(|span| span.label() == "Rpx > SectionHeader[1] > data"));
+
+        let report = rpx.reader.coverage().unwrap();
+        assert!(report.gaps.is_empty());
+        assert!(report.overlaps.is_empty());
+    }
+
+    #[test]
+    fn test_bad_symbol_name() {
+        let mut symtab = TestSection::new(".symtab", SHT_SYMTAB, symbol(0x40, 0, 0, 0, 0));
+        symtab.link = 2;
+        let strtab = TestSection::new(".strtab", SHT_STRTAB, b"\0".to_vec());
+        let data = test_elf::build(vec![symtab, strtab]);
+
+        let error = Rpx::try_parse(BinaryReader::new(&data)).err().unwrap();
+        assert_eq!(
+            error.path_string(),
+            "Rpx > SectionHeader[1] > Symbol[0] > name"
+        );
+    }
+
+    #[test]
+    fn test_vaddr() {
+        let mut text = TestSection::new(".text", 1, vec![0x12, 0x34, 0x56, 0x78, 0x3f, 0x80, 0, 0]);
+        text.flags = 0x6;
+        text.address = 0x0200_0000;
+        let mut bss = TestSection::new(".bss", SHT_NOBITS, Vec::new());
+        bss.flags = 0x3;
+        bss.address = 0x1000_0000;
+        bss.size = Some(0x100);
+        let comment = TestSection::new(".comment", 1, vec![0xff; 4]);
+        let data = test_elf::build(vec![text, bss, comment]);
+        let text_offset = test_elf::HEADER_SIZE + 5 * test_elf::SECTION_HEADER_SIZE;
+
+        let rpx = Rpx::try_parse(BinaryReader::new(&data)).unwrap();
+        assert_eq!(
+            rpx.section_for_vaddr(0x0200_0007).unwrap().name.to_string(),
+            ".text"
+        );
+        assert!(rpx.section_for_vaddr(0x0200_0008).is_none());
+        // unallocated sections have no address even though sh_addr is 0
+        assert!(rpx.section_for_vaddr(0).is_none());
+
+        assert_eq!(&*rpx.read_vaddr(0x0200_0001, 2).unwrap(), &[0x34, 0x56]);
+        assert_eq!(rpx.read_u32_at_vaddr(0x0200_0000).unwrap(), 0x1234_5678);
+        assert_eq!(rpx.read_f32_at_vaddr(0x0200_0004).unwrap(), 1.0);
+        assert_eq!(rpx.read_u64_at_vaddr(0x1000_00f8).unwrap(), 0);
+
+        let error = rpx.read_u32_at_vaddr(0x0200_0006).unwrap_err();
+        assert_eq!(error.kind, ParseErrorKind::UnexpectedEof { available: 2 });
+        assert_eq!(error.offset, text_offset + 6);
+        let error = rpx.read_vaddr(0x1000_00ff, 2).unwrap_err();
+        assert_eq!(error.kind, ParseErrorKind::UnexpectedEof { available: 1 });
+        let error = rpx.read_vaddr(0x0300_0000, 1).unwrap_err();
+        assert_eq!(error.kind, ParseErrorKind::BadPointer(0x0300_0000));
+
+        assert_eq!(rpx.vaddr_to_offset(0x0200_0004), Some(text_offset + 4));
+        assert_eq!(rpx.vaddr_to_offset(0x1000_0000), None);
+        assert_eq!(rpx.offset_to_vaddr(text_offset + 4), Some(0x0200_0004));
+        assert_eq!(rpx.offset_to_vaddr(text_offset + 8), None);
+    }
+
+    #[test]
+    fn test_vaddr_compressed() {
+        let mut code = vec![0; 0x1000];
+        code[0x800..0x804].copy_from_slice(&[0x12, 0x34, 0x56, 0x78]);
+        let mut compressed = (code.len() as u32).to_be_bytes().to_vec();
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(&code).unwrap();
+        compressed.extend(encoder.finish().unwrap());
+        let compressed_size = compressed.len() as u64;
+        let mut text = TestSection::new(".text", 1, compressed);
+        text.flags = 0x0800_0006;
+        text.address = 0x0200_0000;
+        let data = test_elf::build(vec![text]);
+
+        let rpx = Rpx::try_parse(BinaryReader::new(&data)).unwrap();
+        assert_eq!(rpx.read_u32_at_vaddr(0x0200_0800).unwrap(), 0x1234_5678);
+        assert!(rpx.section_for_vaddr(0x0200_0fff).is_some());
+        assert!(rpx.section_for_vaddr(0x0200_1000).is_none());
+        // the file holds compressed bytes, so there is no offset to map to
+        assert_eq!(rpx.vaddr_to_offset(0x0200_0000), None);
+        assert_eq!(rpx.section_headers[1].size, compressed_size);
+    }
+
+    fn relocation_elf(relocations: &[(u32, u32, i32)]) -> Vec<u8> {
+        let mut text = TestSection::new(".text", 1, vec![0; 16]);
+        text.address = 0x0200_0000;
+        let mut symtab = TestSection::new(
+            ".symtab",
+            SHT_SYMTAB,
+            [symbol(0, 0, 0, 0, 0), symbol(1, 0x0200_0000, 16, 0x12, 1)].concat(),
+        );
+        symtab.link = 3;
+        let strtab = TestSection::new(".strtab", SHT_STRTAB, b"\0main\0".to_vec());
+        let mut rela = TestSection::new(
+            ".rela.text",
+            SHT_RELA,
+            relocations
+                .iter()
+                .flat_map(|&(offset, info, addend)| {
+                    [
+                        offset.to_be_bytes(),
+                        info.to_be_bytes(),
+                        addend.to_be_bytes(),
+                    ]
+                    .concat()
+                })
+                .collect(),
+        );
+        rela.link = 2;
+        rela.info = 1;
+        test_elf::build(vec![text, symtab, strtab, rela])
+    }
+
+    #[test]
+    fn test_relocations() {
+        let data = relocation_elf(&[(0x0200_0004, 0x10a, 0), (0x0200_000a, 0x06, 0x10)]);
+        let rpx = Rpx::try_parse(BinaryReader::new(&data)).unwrap();
+
+        let relocations = rpx.relocation_table(4).unwrap();
+        assert_eq!(
+            rpx.section_content(4),
+            Some(SectionContent::Relocations(relocations))
+        );
+        assert_eq!(
+            rpx.section_content(2),
+            Some(SectionContent::Symbols(&rpx.symbols))
+        );
+        assert_eq!(relocations.len(), 2);
+        assert_eq!(relocations[0].relocation_type, RelocationType::REL24);
+        assert_eq!(relocations[0].section, 1);
+        assert_eq!(rpx.relocation_symbol(&relocations[0]).unwrap().name, "main");
+        assert_eq!(relocations[1].relocation_type, RelocationType::ADDR16_HA);
+        assert_eq!(relocations[1].addend, 0x10);
+        assert!(rpx.relocation_symbol(&relocations[1]).is_none());
+
+        let data = relocation_elf(&[(0x0200_0004, 0x50a, 0)]);
+        let rpx = Rpx::try_parse(BinaryReader::new(&data)).unwrap();
+        assert!(rpx.relocations.is_empty());
+        assert_eq!(rpx.symbols.len(), 2);
+        let warnings = rpx.reader.diagnostics.warnings().collect::<Vec<_>>();
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].code, DiagnosticCode::BadSectionContents);
+        assert_eq!(warnings[0].message, "invalid data: symbol 5 out of range");
+        assert_eq!(
+            warnings[0].path.join(" > "),
+            "Rpx > SectionHeader[4] > Relocation[0]"
+        );
+
+        let mut reader = BinaryReader::new(&data);
+        reader.diagnostics.mode = DiagnosticMode::Strict;
+        let error = Rpx::try_parse(reader).err().unwrap();
+        assert_eq!(
+            error.kind,
+            ParseErrorKind::InvalidData("symbol 5 out of range".to_string())
+        );
+        assert_eq!(
+            error.path_string(),
+            "Rpx > SectionHeader[4] > Relocation[0]"
+        );
+    }
+
+    #[test]
+    fn test_imports() {
+        let header = |count: u8| {
+            let mut ret = vec![0, 0, 0, count, 0xde, 0xad, 0xbe, 0xef];
+            ret.extend_from_slice(b"coreinit\0\0\0\0");
+            ret.resize(8 + 12 + 8 * count as usize, 0);
+            ret
+        };
+        let mut fimport = TestSection::new(".fimport_coreinit", SHT_RPL_IMPORTS, header(2));
+        fimport.flags = 0x6;
+        fimport.address = 0x0210_0000;
+        let mut dimport = TestSection::new(".dimport_coreinit", SHT_RPL_IMPORTS, header(1));
+        dimport.flags = 0x3;
+        dimport.address = 0x1010_0000;
+        let mut symtab = TestSection::new(
+            ".symtab",
+            SHT_SYMTAB,
+            [
+                symbol(0, 0, 0, 0, 0),
+                symbol(1, 0x0210_0014, 0, 0x12, 1),
+                symbol(10, 0x0210_001c, 0, 0x12, 1),
+                symbol(20, 0x1010_0014, 0, 0x11, 2),
+            ]
+            .concat(),
+        );
+        symtab.link = 4;
+        let strtab = TestSection::new(
+            ".strtab",
+            SHT_STRTAB,
+            b"\0OSReport\0OSGetTime\0MEMAllocFromDefaultHeap\0".to_vec(),
+        );
+        let data = test_elf::build(vec![fimport, dimport, symtab, strtab]);
+
+        let rpx = Rpx::try_parse(BinaryReader::new(&data)).unwrap();
+        assert_eq!(rpx.imported_libraries(), vec!["coreinit"]);
+        assert_eq!(
+            rpx.section_content(2),
+            Some(SectionContent::Import(&rpx.imports[1]))
+        );
+        assert_eq!(rpx.imports.len(), 2);
+
+        let functions = &rpx.imports[0];
+        assert_eq!(functions.kind, RplKind::Function);
+        assert_eq!(functions.count, 2);
+        assert_eq!(functions.signature, 0xdead_beef);
+        let names = rpx
+            .import_symbols(functions)
+            .map(|symbol| (symbol.name.as_str(), symbol.value))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            vec![("OSReport", 0x0210_0014), ("OSGetTime", 0x0210_001c)]
+        );
+
+        let data = &rpx.imports[1];
+        assert_eq!(data.kind, RplKind::Data);
+        assert_eq!(
+            rpx.import_symbols(data).next().unwrap().name,
+            "MEMAllocFromDefaultHeap"
+        );
+    }
+
+    #[test]
+    fn test_exports() {
+        let mut fexports = vec![0, 0, 0, 2, 0, 0, 0, 0];
+        fexports.extend_from_slice(&[0x02, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x18]);
+        fexports.extend_from_slice(&[0x02, 0x00, 0x00, 0x40, 0x00, 0x00, 0x00, 0x21]);
+        fexports.extend_from_slice(b"OSReport\0OSGetTime\0");
+        let mut fexports = TestSection::new(".fexports", SHT_RPL_EXPORTS, fexports);
+        fexports.flags = 0x6;
+        let mut dexports = vec![0, 0, 0, 1, 0, 0, 0, 0];
+        dexports.extend_from_slice(&[0x10, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x10]);
+        dexports.extend_from_slice(b"MEMAllocFromDefaultHeap\0");
+        let mut dexports = TestSection::new(".dexports", SHT_RPL_EXPORTS, dexports);
+        dexports.flags = 0x2;
+        let data = test_elf::build(vec![fexports, dexports]);
+
+        let rpx = Rpx::try_parse(BinaryReader::new(&data)).unwrap();
+        assert_eq!(rpx.exports.len(), 2);
+        assert_eq!(rpx.exports[0].kind, RplKind::Function);
+        assert_eq!(rpx.exports[1].kind, RplKind::Data);
+        assert_eq!(rpx.export_by_name("OSGetTime").unwrap().value, 0x0200_0040);
+        assert_eq!(
+            rpx.export_at(0x1000_0000).unwrap().name,
+            "MEMAllocFromDefaultHeap"
+        );
+        assert!(rpx.export_by_name("OSFatal").is_none());
+
+        let mut broken =
+            TestSection::new(".fexports", SHT_RPL_EXPORTS, vec![0, 0, 0, 1, 0, 0, 0, 0]);
+        broken.data.extend_from_slice(&[0, 0, 0, 0, 0, 0, 0x10, 0]);
+        let data = test_elf::build(vec![broken]);
+        let rpx = Rpx::try_parse(BinaryReader::new(&data)).unwrap();
+        assert!(rpx.exports.is_empty());
+        let warning = rpx.reader.diagnostics.warnings().next().unwrap();
+        assert_eq!(
+            warning.path.join(" > "),
+            "Rpx > SectionHeader[1] > RplExport[0] > name"
+        );
+    }
+
+    #[test]
+    fn test_file_info() {
+        let data = test_elf::build(Vec::new());
+        let rpx = Rpx::try_parse(BinaryReader::new(&data)).unwrap();
+        assert!(rpx.file_info().is_none());
+
+        let mut file_info = vec![0; 0x60];
+        file_info[..4].copy_from_slice(&[0xca, 0xfe, 0x04, 0x02]);
+        file_info[0x37] = 0x2;
+        let data = test_elf::build(vec![TestSection::new(
+            ".rplfileinfo",
+            SHT_RPL_FILEINFO,
+            file_info,
+        )]);
+        let rpx = Rpx::try_parse(BinaryReader::new(&data)).unwrap();
+        let file_info = rpx.file_info().unwrap();
+        assert_eq!(file_info.version, 0xcafe_0402);
+        assert!(file_info.is_rpx());
+        assert!(file_info.tags.is_empty());
+
+        let mut file_info = vec![0; 0x60];
+        file_info[..4].copy_from_slice(&[0xca, 0xfe, 0x04, 0x01]);
+        let text = TestSection::new(".text", 1, vec![0; 4]);
+        let data = test_elf::build(vec![
+            text,
+            TestSection::new(".rplfileinfo", SHT_RPL_FILEINFO, file_info),
+        ]);
+        let rpx = Rpx::try_parse(BinaryReader::new(&data)).unwrap();
+        assert!(rpx.file_info().is_none());
+        assert_eq!(rpx.section_headers.len(), 4);
+        let warning = rpx.reader.diagnostics.warnings().next().unwrap();
+        assert_eq!(warning.code, DiagnosticCode::BadSectionContents);
+        assert_eq!(
+            warning.offset,
+            test_elf::HEADER_SIZE + 4 * test_elf::SECTION_HEADER_SIZE + 4
+        );
+        assert_eq!(
+            warning.path.join(" > "),
+            "Rpx > SectionHeader[2] > RplFileInfo"
+        );
+    }
+
+    #[test]
+    fn test_crcs() {
+        let text = TestSection::new(".text", 1, b"123456789".to_vec());
+        let mut bss = TestSection::new(".bss", SHT_NOBITS, Vec::new());
+        bss.size = Some(0x100);
+        let crcs = [0u32, 0xcbf4_3926, 0, 0, 0xdead_beef]
+            .iter()
+            .flat_map(|crc| crc.to_be_bytes())
+            .collect();
+        let data = test_elf::build(vec![
+            text,
+            bss,
+            TestSection::new(".rplcrcs", SHT_RPL_CRCS, crcs),
+        ]);
+
+        let mut rpx = Rpx::try_parse(BinaryReader::new(&data)).unwrap();
+        assert_eq!(rpx.crcs.len(), 5);
+        assert_eq!(
+            rpx.section_content(3),
+            Some(SectionContent::Crcs(&rpx.crcs))
+        );
+        assert_eq!(rpx.section_content(2), Some(SectionContent::NoBits(0x100)));
+        assert_eq!(
+            rpx.section_content(1),
+            Some(SectionContent::Bytes(b"123456789"))
+        );
+        assert_eq!(rpx.section_content(0), Some(SectionContent::Null));
+        assert_eq!(rpx.section_content(5), None);
+        let report = rpx.verify_crcs();
+        assert_eq!(report.len(), 5);
+        assert!(report[..4].iter().all(|crc| crc.is_valid()));
+        // the section name table is wrong in the table
+        assert!(!report[4].is_valid());
+        assert_eq!(report[4].expected, Some(0xdead_beef));
+
+        rpx.section_headers[1].data = b"12345678".to_vec().into();
+        assert!(!rpx.verify_crcs()[1].is_valid());
+        rpx.update_crcs();
+        assert!(rpx.verify_crcs().iter().all(|crc| crc.is_valid()));
+        assert_eq!(
+            &rpx.section_headers[3].data[4..8],
+            &rpx.crcs[1].to_be_bytes()
+        );
+    }
+
+    #[test]
+    fn test_crc_count_mismatch() {
+        let text = TestSection::new(".text", 1, b"123456789".to_vec());
+        let crcs = [0u32, 0xcbf4_3926]
+            .iter()
+            .flat_map(|crc| crc.to_be_bytes())
+            .collect();
+        let data = test_elf::build(vec![text, TestSection::new(".rplcrcs", SHT_RPL_CRCS, crcs)]);
+
+        let mut rpx = Rpx::try_parse(BinaryReader::new(&data)).unwrap();
+        let report = rpx.verify_crcs();
+        assert_eq!(report.len(), 4);
+        assert!(report[1].is_valid());
+        assert_eq!(report[2].expected, None);
+        assert_eq!(report[2].actual, Some(0));
+        assert!(!report[2].is_valid());
+        assert!(!report[3].is_valid());
+
+        rpx.crcs = rpx.compute_crcs();
+        rpx.crcs.push(0x1234_5678);
+        let report = rpx.verify_crcs();
+        assert_eq!(report.len(), 5);
+        assert!(report[..4].iter().all(|crc| crc.is_valid()));
+        assert_eq!(report[4].expected, Some(0x1234_5678));
+        assert_eq!(report[4].actual, None);
+        assert!(!report[4].is_valid());
+    }
+}
@@ -0,0 +1,23 @@
+use super::export::RplExports;
+use super::file_info::RplFileInfo;
+use super::import::RplImport;
+use super::relocation::Relocation;
+use super::symbol::Symbol;
+
+/// What a section holds, as decoded while parsing the [`Rpx`](super::Rpx).
+/// Returned by [`Rpx::section_content`](super::Rpx::section_content).
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum SectionContent<'r> {
+    Null,
+    /// Contents of `SHT_PROGBITS` and unknown sections, inflated if needed.
+    Bytes(&'r [u8]),
+    /// Size of a `SHT_NOBITS` section, which reads as zeros.
+    NoBits(u64),
+    Symbols(&'r [Symbol]),
+    Strings(&'r [u8]),
+    Relocations(&'r [Relocation]),
+    Exports(&'r RplExports),
+    Import(&'r RplImport),
+    Crcs(&'r [u32]),
+    FileInfo(&'r RplFileInfo),
+}
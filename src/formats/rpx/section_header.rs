@@ -1,5 +1,6 @@
-use crate::binary_reader::BinaryReader;
+use crate::binary_reader::{BinaryRead, Parsable, ParseErrorKind, ParseResult};
 use flate2::read::ZlibDecoder;
+use std::borrow::Cow;
 use std::io::Read;
 
 #[derive(Debug, PartialEq)]
@@ -17,8 +18,82 @@ impl std::fmt::Display for SectionName {
     }
 }
 
+pub const SHT_NULL: u32 = 0x00;
+pub const SHT_PROGBITS: u32 = 0x01;
+pub const SHT_SYMTAB: u32 = 0x02;
+pub const SHT_STRTAB: u32 = 0x03;
+pub const SHT_RELA: u32 = 0x04;
+pub const SHT_NOBITS: u32 = 0x08;
+pub const SHT_RPL_EXPORTS: u32 = 0x8000_0001;
+pub const SHT_RPL_IMPORTS: u32 = 0x8000_0002;
+pub const SHT_RPL_CRCS: u32 = 0x8000_0003;
+pub const SHT_RPL_FILEINFO: u32 = 0x8000_0004;
+
+/// `sh_type`, with the Cafe OS specific types.
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Hash)]
+pub enum SectionKind {
+    Null,
+    ProgBits,
+    SymTab,
+    StrTab,
+    Rela,
+    NoBits,
+    RplExports,
+    RplImports,
+    RplCrcs,
+    RplFileInfo,
+    Other(u32),
+}
+
+impl From<u32> for SectionKind {
+    fn from(value: u32) -> SectionKind {
+        match value {
+            SHT_NULL => SectionKind::Null,
+            SHT_PROGBITS => SectionKind::ProgBits,
+            SHT_SYMTAB => SectionKind::SymTab,
+            SHT_STRTAB => SectionKind::StrTab,
+            SHT_RELA => SectionKind::Rela,
+            SHT_NOBITS => SectionKind::NoBits,
+            SHT_RPL_EXPORTS => SectionKind::RplExports,
+            SHT_RPL_IMPORTS => SectionKind::RplImports,
+            SHT_RPL_CRCS => SectionKind::RplCrcs,
+            SHT_RPL_FILEINFO => SectionKind::RplFileInfo,
+            _ => SectionKind::Other(value),
+        }
+    }
+}
+
+impl From<SectionKind> for u32 {
+    fn from(value: SectionKind) -> u32 {
+        match value {
+            SectionKind::Null => SHT_NULL,
+            SectionKind::ProgBits => SHT_PROGBITS,
+            SectionKind::SymTab => SHT_SYMTAB,
+            SectionKind::StrTab => SHT_STRTAB,
+            SectionKind::Rela => SHT_RELA,
+            SectionKind::NoBits => SHT_NOBITS,
+            SectionKind::RplExports => SHT_RPL_EXPORTS,
+            SectionKind::RplImports => SHT_RPL_IMPORTS,
+            SectionKind::RplCrcs => SHT_RPL_CRCS,
+            SectionKind::RplFileInfo => SHT_RPL_FILEINFO,
+            SectionKind::Other(value) => value,
+        }
+    }
+}
+
+crate::bit_flags! {
+    /// `sh_flags` bits, including the Cafe OS specific ones.
+    pub struct SectionFlags: u64 {
+        const WRITE = 0x1;
+        const ALLOC = 0x2;
+        const EXECINSTR = 0x4;
+        /// Contents are zlib-compressed behind their `u32` inflated size.
+        const RPL_ZLIB = 0x0800_0000;
+    }
+}
+
 #[derive(Debug)]
-pub struct SectionHeader {
+pub struct SectionHeader<'a> {
     pub name: SectionName,
     pub sh_type: u32,
     pub sh_flags: u64,
@@ -31,10 +106,13 @@ pub struct SectionHeader {
     pub sh_ent_size: u64,
 
     pub remained_flags: u64,
-    pub data: Vec<u8>,
+    /// Section contents. Borrowed from the input unless the section was
+    /// zlib-compressed, in which case it owns the inflated bytes.
+    pub data: Cow<'a, [u8]>,
 }
 
-impl SectionHeader {
+impl<'a> SectionHeader<'a> {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         name: SectionName,
         sh_type: u32,
@@ -46,10 +124,8 @@ impl SectionHeader {
         sh_info: u64,
         sh_addr_align: u64,
         sh_ent_size: u64,
-
-        reader: Option<&mut BinaryReader>,
-    ) -> SectionHeader {
-        let mut ret = SectionHeader {
+    ) -> SectionHeader<'a> {
+        SectionHeader {
             name,
             sh_type,
             sh_flags,
@@ -62,53 +138,85 @@ impl SectionHeader {
             sh_ent_size,
 
             remained_flags: sh_flags,
-            data: vec![],
-        };
-
-        if sh_type == 0x08 {
-            // NO BITS
-            return ret;
+            data: Cow::Borrowed(&[]),
         }
+    }
 
-        if let Some(reader) = reader {
-            ret.data = reader.read(ret.offset as usize, ret.size as usize);
+    pub fn flags(&self) -> SectionFlags {
+        SectionFlags(self.sh_flags)
+    }
+
+    pub fn kind(&self) -> SectionKind {
+        SectionKind::from(self.sh_type)
+    }
+
+    /// Loads the section contents from `reader`, inflating them when the
+    /// section is zlib-compressed.
+    pub fn read_data<R: BinaryRead<'a>>(&mut self, reader: &mut R) -> ParseResult<()> {
+        if self.kind() == SectionKind::NoBits {
+            return Ok(());
         }
 
-        if (ret.sh_flags & 1 << 27) != 0 {
-            // 0x8000000
-            ret.remained_flags &= !(1 << 27);
-            // zlib deflate
-            let mut decoder = ZlibDecoder::new(&ret.data[4..]);
-            let mut buf = vec![];
-            decoder.read_to_end(&mut buf).unwrap();
-            ret.data = buf;
+        self.data = reader.read(self.offset as usize, self.size as usize)?;
+
+        if self.flags().contains(SectionFlags::RPL_ZLIB) {
+            self.remained_flags &= !SectionFlags::RPL_ZLIB.bits();
+            if self.data.len() < 4 {
+                return Err(reader.error_at(
+                    ParseErrorKind::Decompress("missing inflated size".to_string()),
+                    self.offset as usize,
+                    self.size as usize,
+                ));
+            }
+            // the inflated size is stored in front of the zlib stream
+            let inflated_size =
+                u32::from_be_bytes([self.data[0], self.data[1], self.data[2], self.data[3]])
+                    as usize;
+            let max = reader.limits().max_decompressed_size;
+            let mut buf = Vec::with_capacity(reader.check_decompressed_size(inflated_size)?);
+            let mut decoder =
+                ZlibDecoder::new(&self.data[4..]).take((max as u64).saturating_add(1));
+            decoder.read_to_end(&mut buf).map_err(|e| {
+                reader.error_at(
+                    ParseErrorKind::Decompress(e.to_string()),
+                    self.offset as usize,
+                    self.size as usize,
+                )
+            })?;
+            reader.check_decompressed_size(buf.len())?;
+            self.data = Cow::Owned(buf);
         }
 
-        ret
+        Ok(())
     }
+}
 
-    pub fn default() -> SectionHeader {
-        SectionHeader::new(SectionName::Offset(0), 0, 0, 0, 0, 0, 0, 0, 0, 0, None)
+impl Default for SectionHeader<'_> {
+    fn default() -> Self {
+        SectionHeader::new(SectionName::Offset(0), 0, 0, 0, 0, 0, 0, 0, 0, 0)
     }
+}
 
-    pub fn parse(reader: &mut BinaryReader) -> SectionHeader {
-        SectionHeader::new(
-            SectionName::Offset(reader.read_u32() as usize),
-            reader.read_u32(),
-            reader.read_word() as u64,
-            reader.read_addr(),
-            reader.read_addr(),
-            reader.read_word() as u64,
-            reader.read_u32() as u64,
-            reader.read_u32() as u64,
-            reader.read_word() as u64,
-            reader.read_word() as u64,
-            Some(reader),
-        )
+impl<'a> Parsable<'a> for SectionHeader<'a> {
+    fn try_parse<R: BinaryRead<'a>>(reader: &mut R) -> ParseResult<SectionHeader<'a>> {
+        let mut ret = SectionHeader::new(
+            SectionName::Offset(reader.read_u32()? as usize),
+            reader.read_u32()?,
+            reader.read_word()?,
+            reader.read_addr()?,
+            reader.read_addr()?,
+            reader.read_word()?,
+            reader.read_u32()? as u64,
+            reader.read_u32()? as u64,
+            reader.read_word()?,
+            reader.read_word()?,
+        );
+        reader.scoped("data", |reader| ret.read_data(reader))?;
+        Ok(ret)
     }
 }
 
-impl std::fmt::Display for SectionHeader {
+impl std::fmt::Display for SectionHeader<'_> {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         write!(
             f,
@@ -119,8 +227,8 @@ impl std::fmt::Display for SectionHeader {
 }
 #[cfg(test)]
 mod tests {
-    use super::SectionName;
-    use crate::binary_reader::BinaryReader;
+    use super::{SectionFlags, SectionKind, SectionName};
+    use crate::binary_reader::{BinaryReader, Limit, Parsable, ParseErrorKind};
 
     #[test]
     fn test_parse() {
@@ -130,12 +238,13 @@ mod tests {
             0x77, 0x77, 0x77, 0x77, 0x88, 0x88, 0x88, 0x88, 0x99, 0x99, 0x99, 0x99,
         ];
 
-        let mut reader = BinaryReader::new(data.clone());
+        let mut reader = BinaryReader::new(&data);
 
         let header = super::SectionHeader::parse(&mut reader);
 
         assert_eq!(header.name, SectionName::Offset(0x00000000), "[sh_name]");
         assert_eq!(header.sh_type, 0x11111111, "[sh_type]");
+        assert_eq!(header.kind(), SectionKind::Other(0x11111111), "[kind]");
         assert_eq!(header.sh_flags, 0x22222222, "[sh_flags]");
         assert_eq!(header.address, 0x33333333, "[sh_addr]");
         assert_eq!(header.offset, 0, "[sh_offset]");
@@ -145,7 +254,8 @@ mod tests {
         assert_eq!(header.alignment, 0x88888888, "[sh_addr_align]");
         assert_eq!(header.sh_ent_size, 0x99999999, "[sh_ent_size]");
 
-        assert_eq!(header.data, data.clone(), "[data]");
+        assert_eq!(header.data, data, "[data]");
+        assert!(matches!(header.data, std::borrow::Cow::Borrowed(_)));
     }
 
     #[test]
@@ -157,9 +267,40 @@ mod tests {
             0xcb, 0xc8, 0x04, 0x00, 0x01, 0x3b, 0x00, 0xd2,
         ];
 
-        let mut reader = BinaryReader::new(data.clone());
+        let mut reader = BinaryReader::new(&data);
 
         let header = super::SectionHeader::parse(&mut reader);
         assert_eq!(header.data, vec![0x68, 0x69], "[data]");
+        assert!(header.flags().contains(SectionFlags::RPL_ZLIB));
+        assert_eq!(header.kind(), SectionKind::Null);
+        assert_eq!(header.remained_flags, 0, "[remained_flags]");
+
+        let mut reader = BinaryReader::new(&data);
+        reader.limits.max_decompressed_size = 1;
+        let error = super::SectionHeader::try_parse(&mut reader).unwrap_err();
+        assert_eq!(
+            error.kind,
+            ParseErrorKind::LimitExceeded {
+                limit: Limit::DecompressedSize,
+                requested: 2,
+                max: 1,
+            }
+        );
+    }
+
+    #[test]
+    fn test_truncated_data() {
+        let data = vec![
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x20, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        ];
+
+        let mut reader = BinaryReader::new(&data);
+
+        let error = super::SectionHeader::try_parse(&mut reader).unwrap_err();
+        assert_eq!(error.offset, 0x20, "[offset]");
+        assert_eq!(error.length, 0x100, "[length]");
+        assert_eq!(error.path_string(), "data", "[path]");
     }
 }
@@ -0,0 +1,181 @@
+use crate::binary_reader::{BinaryRead, Parsable, ParseResult};
+
+// `st_shndx` values that do not refer to a section
+pub const SHN_UNDEF: u16 = 0;
+pub const SHN_LORESERVE: u16 = 0xff00;
+pub const SHN_ABS: u16 = 0xfff1;
+pub const SHN_COMMON: u16 = 0xfff2;
+
+/// Upper nibble of `st_info`.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum SymbolBinding {
+    Local,
+    Global,
+    Weak,
+    Other(u8),
+}
+
+impl From<u8> for SymbolBinding {
+    fn from(value: u8) -> SymbolBinding {
+        match value {
+            0 => SymbolBinding::Local,
+            1 => SymbolBinding::Global,
+            2 => SymbolBinding::Weak,
+            _ => SymbolBinding::Other(value),
+        }
+    }
+}
+
+/// Lower nibble of `st_info`.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum SymbolType {
+    NoType,
+    Object,
+    Func,
+    Section,
+    File,
+    Other(u8),
+}
+
+impl From<u8> for SymbolType {
+    fn from(value: u8) -> SymbolType {
+        match value {
+            0 => SymbolType::NoType,
+            1 => SymbolType::Object,
+            2 => SymbolType::Func,
+            3 => SymbolType::Section,
+            4 => SymbolType::File,
+            _ => SymbolType::Other(value),
+        }
+    }
+}
+
+/// Lower two bits of `st_other`.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum SymbolVisibility {
+    Default,
+    Internal,
+    Hidden,
+    Protected,
+}
+
+impl From<u8> for SymbolVisibility {
+    fn from(value: u8) -> SymbolVisibility {
+        match value & 0x3 {
+            0 => SymbolVisibility::Default,
+            1 => SymbolVisibility::Internal,
+            2 => SymbolVisibility::Hidden,
+            _ => SymbolVisibility::Protected,
+        }
+    }
+}
+
+/// An entry of a `SHT_SYMTAB` section. `name` is filled in from the linked
+/// string table by [`Rpx`](super::Rpx).
+#[derive(PartialEq, Debug, Clone, Default)]
+pub struct Symbol {
+    pub name: String,
+    pub name_offset: u32,
+    pub value: u64,
+    pub size: u64,
+    pub info: u8,
+    pub other: u8,
+    pub shndx: u16,
+}
+
+impl Symbol {
+    pub fn binding(&self) -> SymbolBinding {
+        SymbolBinding::from(self.info >> 4)
+    }
+
+    pub fn symbol_type(&self) -> SymbolType {
+        SymbolType::from(self.info & 0xf)
+    }
+
+    pub fn visibility(&self) -> SymbolVisibility {
+        SymbolVisibility::from(self.other)
+    }
+
+    /// Whether `shndx` names an actual section rather than being undefined
+    /// or one of the reserved indices.
+    pub fn has_section(&self) -> bool {
+        self.shndx != SHN_UNDEF && self.shndx < SHN_LORESERVE
+    }
+
+    /// Size of one entry in the current ELF class.
+    pub fn entry_size(is_64bit: bool) -> usize {
+        if is_64bit {
+            24
+        } else {
+            16
+        }
+    }
+}
+
+impl<'a> Parsable<'a> for Symbol {
+    fn try_parse<R: BinaryRead<'a>>(reader: &mut R) -> ParseResult<Symbol> {
+        let name_offset = reader.read_u32()?;
+        Ok(if reader.is_64bit() {
+            let info = reader.read_u8()?;
+            let other = reader.read_u8()?;
+            let shndx = reader.read_u16()?;
+            Symbol {
+                name: String::new(),
+                name_offset,
+                info,
+                other,
+                shndx,
+                value: reader.read_u64()?,
+                size: reader.read_u64()?,
+            }
+        } else {
+            Symbol {
+                name: String::new(),
+                name_offset,
+                value: reader.read_u32()? as u64,
+                size: reader.read_u32()? as u64,
+                info: reader.read_u8()?,
+                other: reader.read_u8()?,
+                shndx: reader.read_u16()?,
+            }
+        })
+    }
+}
+
+impl std::fmt::Display for Symbol {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "Symbol[{:?} {:?}] {:#010x}({:#x}) @ {}: {}",
+            self.binding(),
+            self.symbol_type(),
+            self.value,
+            self.size,
+            self.shndx,
+            self.name
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Symbol, SymbolBinding, SymbolType, SymbolVisibility};
+    use crate::binary_reader::{BinaryReader, Parsable};
+
+    #[test]
+    fn test_parse() {
+        let data = vec![
+            0x00, 0x00, 0x00, 0x05, 0x02, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x20, 0x12, 0x02,
+            0x00, 0x03,
+        ];
+        let symbol = Symbol::parse(&mut BinaryReader::new(&data));
+        assert_eq!(symbol.name_offset, 5);
+        assert_eq!(symbol.value, 0x0200_0100);
+        assert_eq!(symbol.size, 0x20);
+        assert_eq!(symbol.shndx, 3);
+        assert_eq!(symbol.binding(), SymbolBinding::Global);
+        assert_eq!(symbol.symbol_type(), SymbolType::Func);
+        assert_eq!(symbol.visibility(), SymbolVisibility::Hidden);
+        assert!(symbol.has_section());
+    }
+}
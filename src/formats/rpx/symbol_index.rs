@@ -0,0 +1,251 @@
+use super::demangle::demangle_name;
+use super::symbol::Symbol;
+use std::collections::HashMap;
+
+/// Symbols that are `[start, end)` address ranges, sorted by start. Each
+/// element is also the root of the implicit subtree over the middle of its
+/// range, which `max_end` augments with the furthest end below it, so a
+/// stabbing query only descends into subtrees that can contain the address.
+#[derive(Debug, Default)]
+struct IntervalTree {
+    starts: Vec<u64>,
+    ends: Vec<u64>,
+    indices: Vec<usize>,
+    max_end: Vec<u64>,
+}
+
+impl IntervalTree {
+    fn new(mut intervals: Vec<(u64, u64, usize)>) -> IntervalTree {
+        intervals.sort_by_key(|&(start, _, index)| (start, index));
+        let mut ret = IntervalTree {
+            starts: intervals.iter().map(|&(start, _, _)| start).collect(),
+            ends: intervals.iter().map(|&(_, end, _)| end).collect(),
+            indices: intervals.iter().map(|&(_, _, index)| index).collect(),
+            max_end: vec![0; intervals.len()],
+        };
+        ret.build(0, intervals.len());
+        ret
+    }
+
+    fn build(&mut self, lo: usize, hi: usize) -> u64 {
+        if lo >= hi {
+            return 0;
+        }
+        let mid = lo + (hi - lo) / 2;
+        let max_end = self.ends[mid]
+            .max(self.build(lo, mid))
+            .max(self.build(mid + 1, hi));
+        self.max_end[mid] = max_end;
+        max_end
+    }
+
+    fn stab<F: FnMut(usize)>(&self, address: u64, lo: usize, hi: usize, visit: &mut F) {
+        if lo >= hi {
+            return;
+        }
+        let mid = lo + (hi - lo) / 2;
+        if self.max_end[mid] <= address {
+            return;
+        }
+        self.stab(address, lo, mid, visit);
+        if self.starts[mid] <= address {
+            if address < self.ends[mid] {
+                visit(mid);
+            }
+            self.stab(address, mid + 1, hi, visit);
+        }
+    }
+}
+
+/// Lookup tables over a symbol list, replacing the linear scans of
+/// `lib/wrapper.py`. Results are indices into the list the index was built
+/// from.
+#[derive(Debug, Default)]
+pub struct SymbolIndex {
+    /// Mangled and demangled names.
+    by_name: HashMap<String, Vec<usize>>,
+    names: Vec<String>,
+    lowercase: Vec<String>,
+    /// Demangled namespace components, lowercased.
+    classes: Vec<Vec<String>>,
+    addresses: Vec<u64>,
+    by_address: Vec<usize>,
+    intervals: IntervalTree,
+}
+
+impl SymbolIndex {
+    pub fn new(symbols: &[Symbol]) -> SymbolIndex {
+        let mut by_name = HashMap::<String, Vec<usize>>::new();
+        let mut classes = Vec::with_capacity(symbols.len());
+        for (i, symbol) in symbols.iter().enumerate() {
+            by_name.entry(symbol.name.clone()).or_default().push(i);
+            let mut path = Vec::new();
+            if let Some(demangled) = demangle_name(&symbol.name) {
+                path = demangled
+                    .to_lowercase()
+                    .split("::")
+                    .map(String::from)
+                    .collect();
+                path.pop();
+                if demangled != symbol.name {
+                    by_name.entry(demangled).or_default().push(i);
+                }
+            }
+            classes.push(path);
+        }
+
+        let mut by_address = (0..symbols.len()).collect::<Vec<_>>();
+        by_address.sort_by_key(|&i| symbols[i].value);
+
+        let intervals = symbols
+            .iter()
+            .enumerate()
+            .filter(|(_, symbol)| symbol.size != 0)
+            .map(|(i, symbol)| (symbol.value, symbol.value.saturating_add(symbol.size), i))
+            .collect();
+
+        SymbolIndex {
+            by_name,
+            names: symbols.iter().map(|symbol| symbol.name.clone()).collect(),
+            lowercase: symbols
+                .iter()
+                .map(|symbol| symbol.name.to_lowercase())
+                .collect(),
+            classes,
+            addresses: by_address.iter().map(|&i| symbols[i].value).collect(),
+            by_address,
+            intervals: IntervalTree::new(intervals),
+        }
+    }
+
+    /// Symbols whose mangled name or demangled qualified name (as given by
+    /// [`demangle_name`]) is exactly `name`.
+    pub fn by_name(&self, name: &str) -> &[usize] {
+        self.by_name.get(name).map_or(&[], Vec::as_slice)
+    }
+
+    /// Symbols whose value is exactly `address`, in symbol table order.
+    pub fn at_address(&self, address: u64) -> &[usize] {
+        let start = self.addresses.partition_point(|&value| value < address);
+        let end = self.addresses.partition_point(|&value| value <= address);
+        &self.by_address[start..end]
+    }
+
+    /// Every sized symbol whose range contains `address`.
+    pub fn containing(&self, address: u64) -> Vec<usize> {
+        let mut ret = Vec::new();
+        let tree = &self.intervals;
+        tree.stab(address, 0, tree.starts.len(), &mut |i| {
+            ret.push(tree.indices[i])
+        });
+        ret.sort_unstable();
+        ret
+    }
+
+    /// The smallest symbol containing `address`, the first in symbol table
+    /// order among equally sized ones.
+    pub fn smallest_containing(&self, address: u64) -> Option<usize> {
+        let tree = &self.intervals;
+        let mut ret: Option<(u64, usize)> = None;
+        tree.stab(address, 0, tree.starts.len(), &mut |i| {
+            let candidate = (tree.ends[i] - tree.starts[i], tree.indices[i]);
+            if ret.is_none_or(|best| candidate < best) {
+                ret = Some(candidate);
+            }
+        });
+        ret.map(|(_, index)| index)
+    }
+
+    /// Case-insensitive substring search over mangled names, sorted by name.
+    /// With a `class`, only symbols declared in a class or namespace of that
+    /// name match, either by demangled path or by the `__<len><class>`
+    /// qualifier `wrapper.py` looks for.
+    pub fn search(&self, class: Option<&str>, query: &str) -> Vec<usize> {
+        let query = query.to_lowercase();
+        let class = class.map(|class| {
+            let class = class.to_lowercase();
+            let qualifier = format!("__{}{}", class.len(), class);
+            (class, qualifier)
+        });
+        let mut ret = (0..self.names.len())
+            .filter(|&i| self.lowercase[i].contains(&query))
+            .filter(|&i| match &class {
+                Some((class, qualifier)) => {
+                    self.classes[i].contains(class) || self.lowercase[i].contains(qualifier)
+                }
+                None => true,
+            })
+            .collect::<Vec<_>>();
+        ret.sort_by(|&a, &b| self.names[a].cmp(&self.names[b]).then(a.cmp(&b)));
+        ret
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SymbolIndex;
+    use crate::formats::rpx::symbol::Symbol;
+
+    fn symbol(name: &str, value: u64, size: u64) -> Symbol {
+        Symbol {
+            name: name.to_string(),
+            value,
+            size,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_lookup() {
+        let symbols = vec![
+            symbol("tick__9MinecraftFv", 0x100, 0x40),
+            symbol("__ct__Q2_2nn3FooFi", 0x140, 0x10),
+            symbol("tick__Q2_2nn3FooFv", 0x120, 0x8),
+            symbol("data", 0x100, 0),
+        ];
+        let index = SymbolIndex::new(&symbols);
+
+        assert_eq!(index.by_name("data"), &[3]);
+        assert_eq!(index.by_name("Minecraft::tick"), &[0]);
+        assert_eq!(index.by_name("nn::Foo::Foo"), &[1]);
+        assert!(index.by_name("missing").is_empty());
+
+        assert_eq!(index.at_address(0x100), &[0, 3]);
+        assert!(index.at_address(0x104).is_empty());
+
+        assert_eq!(index.containing(0x124), vec![0, 2]);
+        assert_eq!(index.smallest_containing(0x124), Some(2));
+        assert_eq!(index.smallest_containing(0x140), Some(1));
+        assert_eq!(index.smallest_containing(0x150), None);
+
+        assert_eq!(index.search(None, "TICK"), vec![0, 2]);
+        assert_eq!(index.search(Some("minecraft"), "tick"), vec![0]);
+        assert_eq!(index.search(Some("nn"), ""), vec![1, 2]);
+    }
+
+    #[test]
+    fn test_many_symbols() {
+        // nested ranges of every size, checked against a linear scan
+        let symbols = (0..100_000u64)
+            .map(|i| symbol(&format!("f{}", i), i * 16 % 40_000, (i % 97 + 1) * 4))
+            .collect::<Vec<_>>();
+        let index = SymbolIndex::new(&symbols);
+
+        for address in (0..41_000).step_by(373) {
+            let expected = symbols
+                .iter()
+                .enumerate()
+                .filter(|(_, s)| s.value <= address && address < s.value + s.size)
+                .min_by_key(|(i, s)| (s.size, *i))
+                .map(|(i, _)| i);
+            assert_eq!(
+                index.smallest_containing(address),
+                expected,
+                "{:#x}",
+                address
+            );
+        }
+        assert_eq!(index.by_name("f99999"), &[99_999]);
+        assert_eq!(index.at_address(0x10).len(), 40);
+    }
+}
@@ -0,0 +1,98 @@
+//! Builds small 32-bit big-endian ELF images for the RPX tests.
+
+use crate::binary_writer::BinaryWriter;
+
+pub struct TestSection {
+    pub name: &'static str,
+    pub sh_type: u32,
+    pub flags: u32,
+    pub address: u32,
+    pub data: Vec<u8>,
+    /// Overrides `data.len()`, for NOBITS sections.
+    pub size: Option<u32>,
+    pub link: u32,
+    pub info: u32,
+    pub ent_size: u32,
+}
+
+impl TestSection {
+    pub fn new(name: &'static str, sh_type: u32, data: Vec<u8>) -> TestSection {
+        TestSection {
+            name,
+            sh_type,
+            flags: 0,
+            address: 0,
+            data,
+            size: None,
+            link: 0,
+            info: 0,
+            ent_size: 0,
+        }
+    }
+}
+
+pub const HEADER_SIZE: usize = 0x34;
+pub const SECTION_HEADER_SIZE: usize = 0x28;
+
+/// Lays out the ELF header, a null section, `sections` and a trailing
+/// `.shstrtab`, followed by the section contents in the same order.
+pub fn build(sections: Vec<TestSection>) -> Vec<u8> {
+    let mut shstrtab = vec![0];
+    let mut names = Vec::new();
+    for section in &sections {
+        names.push(shstrtab.len() as u32);
+        shstrtab.extend_from_slice(section.name.as_bytes());
+        shstrtab.push(0);
+    }
+    let shstrtab_name = shstrtab.len() as u32;
+    shstrtab.extend_from_slice(b".shstrtab\0");
+
+    let count = sections.len() + 2;
+    let mut offset = (HEADER_SIZE + count * SECTION_HEADER_SIZE) as u32;
+
+    let mut writer = BinaryWriter::new();
+    writer.write_n_bytes(b"\x7fELF\x01\x02\x01\xca\xfe\0\0\0\0\0\0\0");
+    writer.write_u16(0xfe01);
+    writer.write_u16(0x14);
+    writer.write_u32(1);
+    writer.write_u32(0);
+    writer.write_u32(0);
+    writer.write_u32(HEADER_SIZE as u32);
+    writer.write_u32(0);
+    writer.write_u16(HEADER_SIZE as u16);
+    writer.write_u16(0x20);
+    writer.write_u16(0);
+    writer.write_u16(SECTION_HEADER_SIZE as u16);
+    writer.write_u16(count as u16);
+    writer.write_u16(count as u16 - 1);
+
+    writer.write_n_bytes(&[0; SECTION_HEADER_SIZE]);
+    let mut section_header = |writer: &mut BinaryWriter, name, section: &TestSection| {
+        let size = section.size.unwrap_or(section.data.len() as u32);
+        for value in [
+            name,
+            section.sh_type,
+            section.flags,
+            section.address,
+            offset,
+            size,
+            section.link,
+            section.info,
+            4,
+            section.ent_size,
+        ] {
+            writer.write_u32(value);
+        }
+        offset += section.data.len() as u32;
+    };
+    for (section, name) in sections.iter().zip(names) {
+        section_header(&mut writer, name, section);
+    }
+    let shstrtab = TestSection::new(".shstrtab", 3, shstrtab);
+    section_header(&mut writer, shstrtab_name, &shstrtab);
+
+    for section in sections.iter().chain([&shstrtab]) {
+        writer.write_n_bytes(&section.data);
+    }
+    writer.into_inner()
+}
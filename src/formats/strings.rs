@@ -0,0 +1,403 @@
+//! String encodings found in Wii U and Legacy Console Edition files.
+//!
+//! Each wrapper reads and writes one on-disk layout and fails on text it
+//! cannot represent instead of replacing characters, so that parsing and
+//! writing a value gives back the same bytes.
+
+use crate::binary_reader::{BinaryRead, Parsable, ParseErrorKind, ParseResult};
+use crate::binary_writer::{BinaryWriter, Writable, WriteResult};
+use crate::formats::common::U16Be;
+
+pub fn decode_utf16(units: &[u16]) -> Result<String, String> {
+    String::from_utf16(units).map_err(|e| e.to_string())
+}
+
+/// Decodes the modified UTF-8 of Java's `DataInput.readUTF`, which NBT uses:
+/// NUL is stored as `C0 80` and characters outside the BMP as two encoded
+/// surrogates. Other overlong forms are rejected, as they would be written
+/// back shorter.
+pub fn decode_java_utf8(bytes: &[u8]) -> Result<String, String> {
+    let mut units = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let continuation = |j: usize| match bytes.get(j) {
+            Some(&b) if b & 0xc0 == 0x80 => Ok((b & 0x3f) as u16),
+            _ => Err(format!("malformed modified UTF-8 sequence at byte {}", i)),
+        };
+        let b = bytes[i];
+        match b {
+            0x01..=0x7f => {
+                units.push(b as u16);
+                i += 1;
+            }
+            0xc0..=0xdf => {
+                let unit = ((b & 0x1f) as u16) << 6 | continuation(i + 1)?;
+                if unit != 0 && unit < 0x80 {
+                    return Err(format!("overlong modified UTF-8 sequence at byte {}", i));
+                }
+                units.push(unit);
+                i += 2;
+            }
+            0xe0..=0xef => {
+                let unit =
+                    ((b & 0x0f) as u16) << 12 | continuation(i + 1)? << 6 | continuation(i + 2)?;
+                if unit < 0x800 {
+                    return Err(format!("overlong modified UTF-8 sequence at byte {}", i));
+                }
+                units.push(unit);
+                i += 3;
+            }
+            _ => {
+                return Err(format!(
+                    "invalid modified UTF-8 byte {:#04x} at byte {}",
+                    b, i
+                ))
+            }
+        }
+    }
+    decode_utf16(&units)
+}
+
+pub fn encode_java_utf8(value: &str) -> Vec<u8> {
+    let mut ret = Vec::with_capacity(value.len());
+    for unit in value.encode_utf16() {
+        match unit {
+            0x01..=0x7f => ret.push(unit as u8),
+            0x00 | 0x80..=0x7ff => {
+                ret.push(0xc0 | (unit >> 6) as u8);
+                ret.push(0x80 | (unit & 0x3f) as u8);
+            }
+            _ => {
+                ret.push(0xe0 | (unit >> 12) as u8);
+                ret.push(0x80 | (unit >> 6 & 0x3f) as u8);
+                ret.push(0x80 | (unit & 0x3f) as u8);
+            }
+        }
+    }
+    ret
+}
+
+fn read_utf8<'a, R: BinaryRead<'a>>(reader: &mut R, length: usize) -> ParseResult<String> {
+    let start = reader.offset();
+    let bytes = reader.read_n_bytes(length)?;
+    String::from_utf8(bytes.to_vec())
+        .map_err(|e| reader.error_at(ParseErrorKind::InvalidData(e.to_string()), start, length))
+}
+
+fn read_utf16_be<'a, R: BinaryRead<'a>>(reader: &mut R, count: usize) -> ParseResult<Vec<u16>> {
+    let bytes = reader.read_n_bytes(count * 2)?;
+    Ok(bytes
+        .chunks_exact(2)
+        .map(|unit| u16::from_be_bytes([unit[0], unit[1]]))
+        .collect())
+}
+
+fn write_utf16_be(
+    writer: &mut BinaryWriter,
+    units: impl IntoIterator<Item = u16>,
+) -> WriteResult<()> {
+    for unit in units {
+        U16Be(unit).write(writer)?;
+    }
+    Ok(())
+}
+
+fn decode_utf16_at<'a, R: BinaryRead<'a>>(
+    reader: &R,
+    units: &[u16],
+    start: usize,
+) -> ParseResult<String> {
+    decode_utf16(units)
+        .map_err(|e| reader.error_at(ParseErrorKind::InvalidData(e), start, units.len() * 2))
+}
+
+macro_rules! string_type {
+    ($($(#[$meta:meta])* $name:ident;)*) => {
+        $(
+            $(#[$meta])*
+            #[derive(PartialEq, Eq, Debug, Clone, Default)]
+            pub struct $name(pub String);
+
+            impl From<&str> for $name {
+                fn from(value: &str) -> Self {
+                    $name(value.to_string())
+                }
+            }
+
+            impl From<String> for $name {
+                fn from(value: String) -> Self {
+                    $name(value)
+                }
+            }
+
+            impl std::fmt::Display for $name {
+                fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                    f.write_str(&self.0)
+                }
+            }
+        )*
+    };
+}
+
+string_type! {
+    /// UTF-8 bytes after a `u16` byte count.
+    Utf8U16;
+    /// UTF-8 bytes after a `u32` byte count.
+    Utf8U32;
+    /// UTF-16BE code units after a big-endian `u16` unit count, whatever the
+    /// byte order of the reader.
+    Utf16U16;
+    /// UTF-8 bytes up to a NUL terminator.
+    NulTerminated;
+    /// Modified UTF-8 after a `u16` byte count, see [`decode_java_utf8`].
+    JavaUtf8;
+}
+
+impl<'a> Parsable<'a> for Utf8U16 {
+    fn try_parse<R: BinaryRead<'a>>(reader: &mut R) -> ParseResult<Self> {
+        let length = reader.read_u16()? as usize;
+        Ok(Utf8U16(read_utf8(reader, length)?))
+    }
+}
+
+impl Writable for Utf8U16 {
+    fn write(&self, writer: &mut BinaryWriter) -> WriteResult<()> {
+        writer.write_u16_string(&self.0)
+    }
+}
+
+impl<'a> Parsable<'a> for Utf8U32 {
+    fn try_parse<R: BinaryRead<'a>>(reader: &mut R) -> ParseResult<Self> {
+        let length = reader.read_u32()? as usize;
+        Ok(Utf8U32(read_utf8(reader, length)?))
+    }
+}
+
+impl Writable for Utf8U32 {
+    fn write(&self, writer: &mut BinaryWriter) -> WriteResult<()> {
+        let length = u32::try_from(self.0.len())
+            .map_err(|_| writer.error(format!("string of {} bytes is too long", self.0.len())))?;
+        writer.write_u32(length);
+        writer.write_n_bytes(self.0.as_bytes());
+        Ok(())
+    }
+}
+
+impl<'a> Parsable<'a> for Utf16U16 {
+    fn try_parse<R: BinaryRead<'a>>(reader: &mut R) -> ParseResult<Self> {
+        let count = U16Be::try_parse(reader)?.get() as usize;
+        let start = reader.offset();
+        let units = read_utf16_be(reader, count)?;
+        Ok(Utf16U16(decode_utf16_at(reader, &units, start)?))
+    }
+}
+
+impl Writable for Utf16U16 {
+    fn write(&self, writer: &mut BinaryWriter) -> WriteResult<()> {
+        let units = self.0.encode_utf16().collect::<Vec<_>>();
+        let count = u16::try_from(units.len())
+            .map_err(|_| writer.error(format!("string of {} units is too long", units.len())))?;
+        U16Be(count).write(writer)?;
+        write_utf16_be(writer, units)
+    }
+}
+
+impl<'a> Parsable<'a> for NulTerminated {
+    fn try_parse<R: BinaryRead<'a>>(reader: &mut R) -> ParseResult<Self> {
+        let start = reader.offset();
+        let mut end = start;
+        while reader.read_at(end, 1)?[0] != 0 {
+            end += 1;
+        }
+        let ret = read_utf8(reader, end - start)?;
+        reader.read_u8()?;
+        Ok(NulTerminated(ret))
+    }
+}
+
+impl Writable for NulTerminated {
+    fn write(&self, writer: &mut BinaryWriter) -> WriteResult<()> {
+        if self.0.contains('\0') {
+            return Err(writer.error("NUL inside a NUL-terminated string"));
+        }
+        writer.write_n_bytes(self.0.as_bytes());
+        writer.write_u8(0);
+        Ok(())
+    }
+}
+
+impl<'a> Parsable<'a> for JavaUtf8 {
+    fn try_parse<R: BinaryRead<'a>>(reader: &mut R) -> ParseResult<Self> {
+        let length = reader.read_u16()? as usize;
+        let start = reader.offset();
+        let bytes = reader.read_n_bytes(length)?;
+        decode_java_utf8(&bytes)
+            .map(JavaUtf8)
+            .map_err(|e| reader.error_at(ParseErrorKind::InvalidData(e), start, length))
+    }
+}
+
+impl Writable for JavaUtf8 {
+    fn write(&self, writer: &mut BinaryWriter) -> WriteResult<()> {
+        let bytes = encode_java_utf8(&self.0);
+        let length = u16::try_from(bytes.len())
+            .map_err(|_| writer.error(format!("string of {} bytes is too long", bytes.len())))?;
+        writer.write_u16(length);
+        writer.write_n_bytes(&bytes);
+        Ok(())
+    }
+}
+
+/// `N` UTF-16BE code units holding a string padded with NULs, like the
+/// `char16 s[64]` names of the hexpat files. Anything but NULs after the
+/// string is rejected, since it could not be written back.
+#[derive(PartialEq, Eq, Debug, Clone, Default)]
+pub struct FixedUtf16<const N: usize>(pub String);
+
+impl<const N: usize> From<&str> for FixedUtf16<N> {
+    fn from(value: &str) -> Self {
+        FixedUtf16(value.to_string())
+    }
+}
+
+impl<const N: usize> std::fmt::Display for FixedUtf16<N> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl<'a, const N: usize> Parsable<'a> for FixedUtf16<N> {
+    fn try_parse<R: BinaryRead<'a>>(reader: &mut R) -> ParseResult<Self> {
+        let start = reader.offset();
+        let units = read_utf16_be(reader, N)?;
+        let end = units.iter().position(|&unit| unit == 0).unwrap_or(N);
+        if let Some(i) = units[end..].iter().position(|&unit| unit != 0) {
+            return Err(reader.error_at(
+                ParseErrorKind::InvalidData("non-zero unit in NUL padding".to_string()),
+                start + (end + i) * 2,
+                2,
+            ));
+        }
+        Ok(FixedUtf16(decode_utf16_at(reader, &units[..end], start)?))
+    }
+}
+
+impl<const N: usize> Writable for FixedUtf16<N> {
+    fn write(&self, writer: &mut BinaryWriter) -> WriteResult<()> {
+        let units = self.0.encode_utf16().collect::<Vec<_>>();
+        if units.len() > N || units.contains(&0) {
+            return Err(writer.error(format!(
+                "{:?} does not fit a {}-unit NUL-padded field",
+                self.0, N
+            )));
+        }
+        write_utf16_be(
+            writer,
+            units.iter().copied().chain(std::iter::repeat(0)).take(N),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        decode_java_utf8, encode_java_utf8, FixedUtf16, JavaUtf8, NulTerminated, Utf16U16, Utf8U16,
+        Utf8U32,
+    };
+    use crate::binary_reader::{BinaryReader, Endian, Parsable};
+    use crate::binary_writer::{BinaryWriter, Writable};
+
+    fn roundtrip<T>(value: T) -> Vec<u8>
+    where
+        T: for<'a> Parsable<'a> + Writable + PartialEq + std::fmt::Debug,
+    {
+        let mut writer = BinaryWriter::new();
+        value.write(&mut writer).unwrap();
+        let data = writer.into_inner();
+        let mut reader = BinaryReader::new(&data);
+        assert_eq!(T::parse(&mut reader), value);
+        assert_eq!(reader.offset, data.len());
+        data
+    }
+
+    #[test]
+    fn test_prefixed() {
+        assert_eq!(roundtrip(Utf8U16::from("hé")), b"\x00\x03h\xc3\xa9");
+        assert_eq!(roundtrip(Utf8U32::from("a")), b"\x00\x00\x00\x01a");
+        assert_eq!(
+            roundtrip(Utf16U16::from("a\u{1f600}")),
+            b"\x00\x03\x00a\xd8\x3d\xde\x00"
+        );
+
+        let data = b"\x00\x02\xc3\x28";
+        let error = Utf8U16::try_parse(&mut BinaryReader::new(data)).unwrap_err();
+        assert_eq!(error.offset, 2);
+        assert_eq!(error.length, 2);
+    }
+
+    #[test]
+    fn test_utf16_big_endian() {
+        let data = b"\x00\x01\x00a\x00b\x00\x00";
+        let mut reader = BinaryReader::new(data);
+        reader.endian = Endian::Little;
+        assert_eq!(Utf16U16::parse(&mut reader).0, "a");
+        assert_eq!(FixedUtf16::<2>::parse(&mut reader).0, "b");
+
+        let mut writer = BinaryWriter::new();
+        writer.endian = Endian::Little;
+        Utf16U16::from("a").write(&mut writer).unwrap();
+        assert_eq!(writer.into_inner(), b"\x00\x01\x00a");
+    }
+
+    #[test]
+    fn test_utf16_unpaired_surrogate() {
+        let data = [0x00, 0x01, 0xd8, 0x00];
+        assert!(Utf16U16::try_parse(&mut BinaryReader::new(&data)).is_err());
+    }
+
+    #[test]
+    fn test_nul_terminated() {
+        assert_eq!(roundtrip(NulTerminated::from("abc")), b"abc\0");
+        assert!(NulTerminated::try_parse(&mut BinaryReader::new(b"abc")).is_err());
+
+        let mut writer = BinaryWriter::new();
+        assert!(NulTerminated::from("a\0b").write(&mut writer).is_err());
+    }
+
+    #[test]
+    fn test_fixed_utf16() {
+        let data = roundtrip(FixedUtf16::<4>::from("ab"));
+        assert_eq!(data, b"\x00a\x00b\x00\x00\x00\x00");
+        roundtrip(FixedUtf16::<2>::from("ab"));
+
+        let mut writer = BinaryWriter::new();
+        assert!(FixedUtf16::<1>::from("ab").write(&mut writer).is_err());
+
+        let data = b"\x00a\x00\x00\x00b\x00\x00";
+        let error = FixedUtf16::<4>::try_parse(&mut BinaryReader::new(data)).unwrap_err();
+        assert_eq!(error.offset, 4);
+        assert_eq!(error.length, 2);
+    }
+
+    #[test]
+    fn test_java_utf8() {
+        assert_eq!(encode_java_utf8("a\0"), b"a\xc0\x80");
+        assert_eq!(encode_java_utf8("\u{1f600}"), b"\xed\xa0\xbd\xed\xb8\x80");
+        assert_eq!(
+            decode_java_utf8(b"\xed\xa0\xbd\xed\xb8\x80").unwrap(),
+            "\u{1f600}"
+        );
+        assert!(decode_java_utf8(b"a\0").is_err());
+        assert!(decode_java_utf8(b"\xc3").is_err());
+        assert!(decode_java_utf8(b"\xed\xa0\xbd").is_err());
+        // overlong forms other than the NUL
+        assert_eq!(decode_java_utf8(b"\xc0\x80").unwrap(), "\0");
+        assert!(decode_java_utf8(b"\xc1\x81").is_err());
+        assert!(decode_java_utf8(b"\xc0\x81").is_err());
+        assert!(decode_java_utf8(b"\xe0\x81\x81").is_err());
+        assert!(decode_java_utf8(b"\xe0\x9f\xbf").is_err());
+        assert_eq!(decode_java_utf8(b"\xe0\xa0\x80").unwrap(), "\u{800}");
+
+        roundtrip(JavaUtf8::from("é\0\u{1f600}"));
+    }
+}
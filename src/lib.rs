@@ -1,4 +1,8 @@
-mod binary_reader;
-mod formats;
-mod string_reader;
-mod utils;
+// Lets the derive macros refer to `::wiiu` from inside this crate too.
+extern crate self as wiiu;
+
+pub mod binary_reader;
+pub mod binary_writer;
+pub mod formats;
+pub mod string_reader;
+pub mod utils;
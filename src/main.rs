@@ -1,13 +1,21 @@
-use std::fs;
+use std::fs::File;
 
-mod binary_reader;
-mod formats;
-mod string_reader;
-mod utils;
+use memmap2::Mmap;
+use wiiu::binary_reader::BinaryReader;
+use wiiu::formats::rpx::Rpx;
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let reader = binary_reader::BinaryReader::new(fs::read("ram/Minecraft.Client.rpx")?);
-    let _rpx = formats::rpx::Rpx::parse(reader);
+    let file = File::open("ram/Minecraft.Client.rpx")?;
+    // SAFETY: the file is opened read-only and not modified while mapped.
+    let data = unsafe { Mmap::map(&file)? };
+    let reader = BinaryReader::new(&data);
+    let rpx = Rpx::try_parse(reader)?;
+    for diagnostic in rpx.reader.diagnostics.warnings() {
+        eprintln!("{}", diagnostic);
+    }
+    for crc in rpx.verify_crcs().iter().filter(|crc| !crc.is_valid()) {
+        eprintln!("{}", crc);
+    }
 
     Ok(())
 }
@@ -5,10 +5,7 @@ pub struct StringReader {
 
 impl StringReader {
     pub fn new(data: Vec<u8>) -> StringReader {
-        StringReader {
-            data: data,
-            offset: 0,
-        }
+        StringReader { data, offset: 0 }
     }
 }
 
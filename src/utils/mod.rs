@@ -1,7 +1,3 @@
-mod concat_number;
-mod find_zero;
 mod rev_32;
 
-pub use concat_number::concat_number;
-pub use find_zero::find_zero;
 pub use rev_32::rev_32;
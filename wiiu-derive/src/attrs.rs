@@ -0,0 +1,81 @@
+use syn::{Attribute, Expr, Type};
+
+#[derive(Clone, Copy)]
+pub enum Endian {
+    Big,
+    Little,
+}
+
+/// Options given through `#[binary(...)]` on a struct.
+#[derive(Default)]
+pub struct ContainerAttrs {
+    pub endian: Option<Endian>,
+    pub magic: Option<Expr>,
+}
+
+/// Options given through `#[binary(...)]` on a field.
+#[derive(Default)]
+pub struct FieldAttrs {
+    pub endian: Option<Endian>,
+    pub len: Option<Type>,
+    pub count: Option<Expr>,
+    pub magic: Option<Expr>,
+    pub pad_before: Option<Expr>,
+    pub pad_after: Option<Expr>,
+}
+
+impl ContainerAttrs {
+    pub fn parse(attrs: &[Attribute]) -> syn::Result<ContainerAttrs> {
+        let mut ret = ContainerAttrs::default();
+        for attr in attrs.iter().filter(|attr| attr.path().is_ident("binary")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("big") {
+                    ret.endian = Some(Endian::Big);
+                } else if meta.path.is_ident("little") {
+                    ret.endian = Some(Endian::Little);
+                } else if meta.path.is_ident("magic") {
+                    ret.magic = Some(meta.value()?.parse()?);
+                } else {
+                    return Err(meta.error("unsupported struct attribute"));
+                }
+                Ok(())
+            })?;
+        }
+        Ok(ret)
+    }
+}
+
+impl FieldAttrs {
+    pub fn parse(attrs: &[Attribute]) -> syn::Result<FieldAttrs> {
+        let mut ret = FieldAttrs::default();
+        for attr in attrs.iter().filter(|attr| attr.path().is_ident("binary")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("big") {
+                    ret.endian = Some(Endian::Big);
+                } else if meta.path.is_ident("little") {
+                    ret.endian = Some(Endian::Little);
+                } else if meta.path.is_ident("len") {
+                    ret.len = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("count") {
+                    ret.count = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("magic") {
+                    ret.magic = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("pad_before") {
+                    ret.pad_before = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("pad_after") {
+                    ret.pad_after = Some(meta.value()?.parse()?);
+                } else {
+                    return Err(meta.error("unsupported field attribute"));
+                }
+                Ok(())
+            })?;
+            if ret.len.is_some() && ret.count.is_some() {
+                return Err(syn::Error::new_spanned(
+                    attr,
+                    "`len` and `count` are mutually exclusive",
+                ));
+            }
+        }
+        Ok(ret)
+    }
+}
@@ -0,0 +1,467 @@
+//! Derive macros generating `Parsable` and `Writable` implementations for
+//! the `wiiu` crate from a declarative struct layout.
+//!
+//! Fields are read and written in declaration order. The layout is tuned
+//! with `#[binary(...)]` attributes:
+//!
+//! - on the struct: `big` / `little` to force the byte order of every field,
+//!   `magic = b"..."` to check (and emit) a signature before the first field;
+//! - on a field: `big` / `little`, `len = u16` (or any integer type) for a
+//!   length-prefixed `Vec<T>` or `String`, `count = expr` for a `Vec<T>` or
+//!   `String` whose length is computed from earlier fields (and checked
+//!   against them when writing), `magic = b"..."`
+//!   to validate a fixed `[u8; N]` field, and `pad_before = n` /
+//!   `pad_after = n` to skip (or zero-fill) bytes around it.
+
+mod attrs;
+
+use attrs::{ContainerAttrs, Endian, FieldAttrs};
+use proc_macro::TokenStream;
+use proc_macro2::{Ident, TokenStream as TokenStream2, TokenTree};
+use quote::quote;
+use syn::{
+    parse_macro_input, parse_quote, Data, DeriveInput, Field, Fields, GenericArgument,
+    GenericParam, Lifetime, PathArguments, Type,
+};
+
+#[proc_macro_derive(Parsable, attributes(binary))]
+pub fn derive_parsable(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_parsable(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+#[proc_macro_derive(Writable, attributes(binary))]
+pub fn derive_writable(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_writable(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// How a field with `len` or `count` is laid out.
+enum Sequence<'a> {
+    Bytes,
+    Text,
+    Elements(&'a Type),
+}
+
+fn sequence(ty: &Type) -> Option<Sequence<'_>> {
+    let Type::Path(path) = ty else {
+        return None;
+    };
+    let segment = path.path.segments.last()?;
+    if segment.ident == "String" {
+        return Some(Sequence::Text);
+    }
+    if segment.ident != "Vec" {
+        return None;
+    }
+    let PathArguments::AngleBracketed(args) = &segment.arguments else {
+        return None;
+    };
+    match args.args.first()? {
+        GenericArgument::Type(Type::Path(elem)) if elem.path.is_ident("u8") => {
+            Some(Sequence::Bytes)
+        }
+        GenericArgument::Type(elem) => Some(Sequence::Elements(elem)),
+        _ => None,
+    }
+}
+
+fn named_fields(input: &DeriveInput) -> syn::Result<Vec<(&Field, FieldAttrs)>> {
+    let Data::Struct(data) = &input.data else {
+        return Err(syn::Error::new_spanned(
+            input,
+            "only structs can derive binary layouts",
+        ));
+    };
+    let Fields::Named(fields) = &data.fields else {
+        return Err(syn::Error::new_spanned(
+            input,
+            "only structs with named fields can derive binary layouts",
+        ));
+    };
+    fields
+        .named
+        .iter()
+        .map(|field| {
+            let attrs = FieldAttrs::parse(&field.attrs)?;
+            if (attrs.len.is_some() || attrs.count.is_some()) && sequence(&field.ty).is_none() {
+                return Err(syn::Error::new_spanned(
+                    &field.ty,
+                    "`len` and `count` require a `Vec<T>` or `String` field",
+                ));
+            }
+            Ok((field, attrs))
+        })
+        .collect()
+}
+
+/// Identifiers appearing anywhere in `tokens`.
+fn idents(tokens: TokenStream2, found: &mut Vec<Ident>) {
+    for token in tokens {
+        match token {
+            TokenTree::Ident(ident) => found.push(ident),
+            TokenTree::Group(group) => idents(group.stream(), found),
+            _ => {}
+        }
+    }
+}
+
+fn endian_tokens(endian: Endian) -> TokenStream2 {
+    match endian {
+        Endian::Big => quote!(::wiiu::binary_reader::Endian::Big),
+        Endian::Little => quote!(::wiiu::binary_reader::Endian::Little),
+    }
+}
+
+fn expand_parsable(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let container = ContainerAttrs::parse(&input.attrs)?;
+    let fields = named_fields(input)?;
+
+    // Borrow from the input with the struct's own lifetime if it has one.
+    let mut generics = input.generics.clone();
+    let lifetime = match generics.lifetimes().next() {
+        Some(param) => param.lifetime.clone(),
+        None => {
+            let lifetime = Lifetime::new("'__wiiu", proc_macro2::Span::call_site());
+            generics
+                .params
+                .insert(0, GenericParam::Lifetime(parse_quote!(#lifetime)));
+            lifetime
+        }
+    };
+    for param in generics.type_params_mut() {
+        param
+            .bounds
+            .push(parse_quote!(::wiiu::binary_reader::Parsable<#lifetime>));
+    }
+    let (impl_generics, _, where_clause) = generics.split_for_impl();
+    let (_, ty_generics, _) = input.generics.split_for_impl();
+    let name = &input.ident;
+
+    let mut steps = Vec::new();
+    if let Some(magic) = &container.magic {
+        steps.push(quote! {
+            let __magic: &[u8] = &(#magic)[..];
+            let __start = reader.offset();
+            let __found = reader.read_n_bytes(__magic.len())?;
+            if *__found != *__magic {
+                return Err(reader.error_at(
+                    ::wiiu::binary_reader::ParseErrorKind::InvalidData(format!(
+                        "bad magic: expected {:02x?}, found {:02x?}",
+                        __magic, &*__found
+                    )),
+                    __start,
+                    __magic.len(),
+                ));
+            }
+        });
+    }
+
+    for (field, attrs) in &fields {
+        let ident = field.ident.as_ref().unwrap();
+        let ty = &field.ty;
+        let label = ident.to_string();
+
+        if let Some(pad) = &attrs.pad_before {
+            steps.push(quote! {
+                reader.read_n_bytes((#pad) as usize)?;
+            });
+        }
+
+        let length = if let Some(len) = &attrs.len {
+            Some(quote! {
+                let __n = <#len as ::wiiu::binary_reader::Parsable<#lifetime>>::try_parse(reader)? as usize;
+            })
+        } else {
+            attrs.count.as_ref().map(|count| {
+                quote! {
+                    let __n = (#count) as usize;
+                }
+            })
+        };
+
+        let read = match length {
+            None => quote! {
+                <#ty as ::wiiu::binary_reader::Parsable<#lifetime>>::try_parse(reader)?
+            },
+            Some(length) => {
+                let collect = match sequence(ty).unwrap() {
+                    Sequence::Bytes => quote! {
+                        reader.read_n_bytes(__n)?.to_vec()
+                    },
+                    Sequence::Text => quote! {{
+                        let __start = reader.offset();
+                        let __bytes = reader.read_n_bytes(__n)?;
+                        String::from_utf8(__bytes.to_vec()).map_err(|e| {
+                            reader.error_at(
+                                ::wiiu::binary_reader::ParseErrorKind::InvalidData(e.to_string()),
+                                __start,
+                                __n,
+                            )
+                        })?
+                    }},
+                    Sequence::Elements(elem) => quote! {{
+                        let mut __elements = Vec::with_capacity(
+                            reader.check_count::<#elem>(__n)?.min(reader.remaining()),
+                        );
+                        for _ in 0..__n {
+                            __elements.push(
+                                <#elem as ::wiiu::binary_reader::Parsable<#lifetime>>::try_parse(reader)?,
+                            );
+                        }
+                        __elements
+                    }},
+                };
+                quote! {{
+                    #length
+                    #collect
+                }}
+            }
+        };
+
+        let check = attrs.magic.as_ref().map(|magic| {
+            quote! {
+                let __magic: &[u8] = &(#magic)[..];
+                if __value[..] != *__magic {
+                    return Err(reader.error_at(
+                        ::wiiu::binary_reader::ParseErrorKind::InvalidData(format!(
+                            "bad magic: expected {:02x?}, found {:02x?}",
+                            __magic, &__value[..]
+                        )),
+                        __start,
+                        reader.offset() - __start,
+                    ));
+                }
+            }
+        });
+
+        let (set_endian, restore_endian) = match attrs.endian {
+            Some(endian) => {
+                let endian = endian_tokens(endian);
+                (
+                    quote! {
+                        let __saved = reader.endian();
+                        reader.set_endian(#endian);
+                    },
+                    quote! {
+                        reader.set_endian(__saved);
+                    },
+                )
+            }
+            None => (quote!(), quote!()),
+        };
+
+        steps.push(quote! {
+            let #ident: #ty = {
+                let __depth = reader.path().len();
+                #set_endian
+                let __ret = (|reader: &mut __R| -> ::wiiu::binary_reader::ParseResult<#ty> {
+                    let __start = reader.offset();
+                    let __value: #ty = #read;
+                    #check
+                    let _ = __start;
+                    Ok(__value)
+                })(reader);
+                #restore_endian
+                __ret.map_err(|mut e| {
+                    e.path.insert(__depth.min(e.path.len()), #label.to_string());
+                    e
+                })?
+            };
+        });
+
+        if let Some(pad) = &attrs.pad_after {
+            steps.push(quote! {
+                reader.read_n_bytes((#pad) as usize)?;
+            });
+        }
+    }
+
+    let idents = fields
+        .iter()
+        .map(|(field, _)| field.ident.as_ref().unwrap());
+    let body = quote! {
+        #(#steps)*
+        Ok(#name { #(#idents),* })
+    };
+    let body = match container.endian {
+        Some(endian) => {
+            let endian = endian_tokens(endian);
+            quote! {
+                let __saved = reader.endian();
+                reader.set_endian(#endian);
+                let __ret = (|reader: &mut __R| -> ::wiiu::binary_reader::ParseResult<Self> {
+                    #body
+                })(reader);
+                reader.set_endian(__saved);
+                __ret
+            }
+        }
+        None => body,
+    };
+
+    Ok(quote! {
+        impl #impl_generics ::wiiu::binary_reader::Parsable<#lifetime> for #name #ty_generics #where_clause {
+            fn try_parse<__R: ::wiiu::binary_reader::BinaryRead<#lifetime>>(
+                reader: &mut __R,
+            ) -> ::wiiu::binary_reader::ParseResult<Self> {
+                #[allow(unused_imports)]
+                use ::wiiu::binary_reader::BinaryRead as _;
+                #body
+            }
+        }
+    })
+}
+
+fn expand_writable(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let container = ContainerAttrs::parse(&input.attrs)?;
+    let fields = named_fields(input)?;
+
+    let mut generics = input.generics.clone();
+    for param in generics.type_params_mut() {
+        param
+            .bounds
+            .push(parse_quote!(::wiiu::binary_writer::Writable));
+    }
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let name = &input.ident;
+
+    let mut steps = Vec::new();
+    if let Some(magic) = &container.magic {
+        steps.push(quote! {
+            writer.write_n_bytes(&(#magic)[..]);
+        });
+    }
+
+    for (field, attrs) in &fields {
+        let ident = field.ident.as_ref().unwrap();
+        let label = ident.to_string();
+
+        if let Some(pad) = &attrs.pad_before {
+            steps.push(quote! {
+                writer.write_n_bytes(&vec![0; (#pad) as usize]);
+            });
+        }
+
+        let prefix = attrs.len.as_ref().map(|len| {
+            quote! {
+                let __n = <#len>::try_from(self.#ident.len()).map_err(|_| {
+                    writer.error(format!("{} is too long for its length prefix", #label))
+                })?;
+                ::wiiu::binary_writer::Writable::write(&__n, writer)?;
+            }
+        });
+
+        // `count` reads earlier fields as locals, as the parser binds them
+        let check_count = attrs.count.as_ref().map(|count| {
+            let mut mentioned = Vec::new();
+            idents(quote!(#count), &mut mentioned);
+            let bindings = fields
+                .iter()
+                .filter_map(|(field, _)| field.ident.as_ref())
+                .filter(|field| mentioned.contains(field))
+                .map(|field| {
+                    quote! {
+                        #[allow(unused_variables, clippy::clone_on_copy)]
+                        let #field = ::core::clone::Clone::clone(&self.#field);
+                    }
+                });
+            quote! {{
+                #(#bindings)*
+                let __n = (#count) as usize;
+                if self.#ident.len() != __n {
+                    return Err(writer.error(format!(
+                        "{} has length {} but its count is {}",
+                        #label,
+                        self.#ident.len(),
+                        __n
+                    )));
+                }
+            }}
+        });
+
+        let write = if attrs.len.is_none() && attrs.count.is_none() {
+            quote! {
+                ::wiiu::binary_writer::Writable::write(&self.#ident, writer)?;
+            }
+        } else {
+            match sequence(&field.ty).unwrap() {
+                Sequence::Bytes => quote! {
+                    writer.write_n_bytes(&self.#ident);
+                },
+                Sequence::Text => quote! {
+                    writer.write_n_bytes(self.#ident.as_bytes());
+                },
+                Sequence::Elements(_) => quote! {
+                    for __element in &self.#ident {
+                        ::wiiu::binary_writer::Writable::write(__element, writer)?;
+                    }
+                },
+            }
+        };
+
+        steps.push(match attrs.endian {
+            Some(endian) => {
+                let endian = endian_tokens(endian);
+                quote! {{
+                    let __saved = writer.endian;
+                    writer.endian = #endian;
+                    let __ret = (|writer: &mut ::wiiu::binary_writer::BinaryWriter| -> ::wiiu::binary_writer::WriteResult<()> {
+                        #check_count
+                        #prefix
+                        #write
+                        Ok(())
+                    })(writer);
+                    writer.endian = __saved;
+                    __ret?;
+                }}
+            }
+            None => quote! {
+                #check_count
+                #prefix
+                #write
+            },
+        });
+
+        if let Some(pad) = &attrs.pad_after {
+            steps.push(quote! {
+                writer.write_n_bytes(&vec![0; (#pad) as usize]);
+            });
+        }
+    }
+
+    let body = quote! {
+        #(#steps)*
+        Ok(())
+    };
+    let body = match container.endian {
+        Some(endian) => {
+            let endian = endian_tokens(endian);
+            quote! {
+                let __saved = writer.endian;
+                writer.endian = #endian;
+                let __ret = (|writer: &mut ::wiiu::binary_writer::BinaryWriter| -> ::wiiu::binary_writer::WriteResult<()> {
+                    #body
+                })(writer);
+                writer.endian = __saved;
+                __ret
+            }
+        }
+        None => body,
+    };
+
+    Ok(quote! {
+        impl #impl_generics ::wiiu::binary_writer::Writable for #name #ty_generics #where_clause {
+            fn write(
+                &self,
+                writer: &mut ::wiiu::binary_writer::BinaryWriter,
+            ) -> ::wiiu::binary_writer::WriteResult<()> {
+                #body
+            }
+        }
+    })
+}